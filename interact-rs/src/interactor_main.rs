@@ -405,13 +405,14 @@ impl ContractInteract {
         let token_amount = BigUint::<StaticApi>::from(0u128);
         let prev_lock_epochs = 0u64;
         let new_lock_epochs = 0u64;
+        let opt_user = OptionalValue::Some(bech32::decode(""));
 
         let result_value = self
             .interactor
             .query()
             .to(self.state.current_address())
             .typed(proxy::SimpleLockEnergyProxy)
-            .calculate_penalty_amount(token_amount, prev_lock_epochs, new_lock_epochs)
+            .calculate_penalty_amount(token_amount, prev_lock_epochs, new_lock_epochs, opt_user)
             .returns(ReturnsResultUnmanaged)
             .prepare_async()
             .run()
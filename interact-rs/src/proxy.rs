@@ -87,6 +87,8 @@ where
     To: TxTo<Env>,
     Gas: TxGas<Env>,
 {
+    /// The first upgrade since the total energy exists pauses the contract, 
+    /// until the owner adds the users with entries saved beforehand, see `addUsersToTotalEnergy`. 
     pub fn upgrade(
         self,
     ) -> TxTypedUpgrade<Env, From, To, NotPayable, Gas, ()> {
@@ -234,6 +236,337 @@ where
             .original_result()
     }
 
+    /// Returns the updated energy entries, in the same order as the given users. 
+    /// Accepts at most `MAX_USERS_PER_ENERGY_QUERY` users. 
+    pub fn get_energy_entries_for_users<
+        Arg0: ProxyArg<MultiValueEncoded<Env::Api, ManagedAddress<Env::Api>>>,
+    >(
+        self,
+        users: Arg0,
+    ) -> TxTypedCall<Env, From, To, NotPayable, Gas, MultiValueEncoded<Env::Api, Energy<Env::Api>>> {
+        self.wrapped_tx
+            .payment(NotPayable)
+            .raw_call("getEnergyEntriesForUsers")
+            .argument(&users)
+            .original_result()
+    }
+
+    /// Returns the energy amounts, in the same order as the given users. 
+    /// Accepts at most `MAX_USERS_PER_ENERGY_QUERY` users. 
+    pub fn get_energy_amounts_for_users<
+        Arg0: ProxyArg<MultiValueEncoded<Env::Api, ManagedAddress<Env::Api>>>,
+    >(
+        self,
+        users: Arg0,
+    ) -> TxTypedCall<Env, From, To, NotPayable, Gas, MultiValueEncoded<Env::Api, BigUint<Env::Api>>> {
+        self.wrapped_tx
+            .payment(NotPayable)
+            .raw_call("getEnergyAmountsForUsers")
+            .argument(&users)
+            .original_result()
+    }
+
+    /// The sum of all the users' energy amounts 
+    pub fn get_total_energy(
+        self,
+    ) -> TxTypedCall<Env, From, To, NotPayable, Gas, BigUint<Env::Api>> {
+        self.wrapped_tx
+            .payment(NotPayable)
+            .raw_call("getTotalEnergy")
+            .original_result()
+    }
+
+    pub fn get_total_locked_tokens(
+        self,
+    ) -> TxTypedCall<Env, From, To, NotPayable, Gas, BigUint<Env::Api>> {
+        self.wrapped_tx
+            .payment(NotPayable)
+            .raw_call("getTotalLockedTokens")
+            .original_result()
+    }
+
+    /// Returns the energy the user had at the given past (or current) epoch, 
+    /// by depleting the last checkpoint recorded before it up to that epoch. 
+    pub fn get_energy_at_epoch<
+        Arg0: ProxyArg<ManagedAddress<Env::Api>>,
+        Arg1: ProxyArg<u64>,
+    >(
+        self,
+        user: Arg0,
+        epoch: Arg1,
+    ) -> TxTypedCall<Env, From, To, NotPayable, Gas, BigUint<Env::Api>> {
+        self.wrapped_tx
+            .payment(NotPayable)
+            .raw_call("getEnergyAtEpoch")
+            .argument(&user)
+            .argument(&epoch)
+            .original_result()
+    }
+
+    /// Sets how energy decays: 
+    /// - Continuous: every epoch (default) 
+    /// - Weekly: every 7 epochs, at epochs divisible by 7. 
+    ///     Energy is then computed as of the start of the current week. 
+    pub fn set_energy_decay_mode<
+        Arg0: ProxyArg<EnergyDecayMode>,
+    >(
+        self,
+        decay_mode: Arg0,
+    ) -> TxTypedCall<Env, From, To, NotPayable, Gas, ()> {
+        self.wrapped_tx
+            .payment(NotPayable)
+            .raw_call("setEnergyDecayMode")
+            .argument(&decay_mode)
+            .original_result()
+    }
+
+    pub fn energy_decay_mode(
+        self,
+    ) -> TxTypedCall<Env, From, To, NotPayable, Gas, EnergyDecayMode> {
+        self.wrapped_tx
+            .payment(NotPayable)
+            .raw_call("getEnergyDecayMode")
+            .original_result()
+    }
+
+    /// Delegates the caller's whole energy to the given address, replacing any previous delegatee. 
+    /// The delegated energy follows any further updates of the caller's energy entry. 
+    /// Energy delegated to an address is not delegated further by it. 
+    pub fn delegate_energy<
+        Arg0: ProxyArg<ManagedAddress<Env::Api>>,
+    >(
+        self,
+        delegatee: Arg0,
+    ) -> TxTypedCall<Env, From, To, NotPayable, Gas, ()> {
+        self.wrapped_tx
+            .payment(NotPayable)
+            .raw_call("delegateEnergy")
+            .argument(&delegatee)
+            .original_result()
+    }
+
+    pub fn undelegate_energy(
+        self,
+    ) -> TxTypedCall<Env, From, To, NotPayable, Gas, ()> {
+        self.wrapped_tx
+            .payment(NotPayable)
+            .raw_call("undelegateEnergy")
+            .original_result()
+    }
+
+    pub fn get_energy_delegatee<
+        Arg0: ProxyArg<ManagedAddress<Env::Api>>,
+    >(
+        self,
+        user: Arg0,
+    ) -> TxTypedCall<Env, From, To, NotPayable, Gas, OptionalValue<ManagedAddress<Env::Api>>> {
+        self.wrapped_tx
+            .payment(NotPayable)
+            .raw_call("getEnergyDelegatee")
+            .argument(&user)
+            .original_result()
+    }
+
+    /// The energy other users delegated to the given address 
+    pub fn get_delegated_energy_for_user<
+        Arg0: ProxyArg<ManagedAddress<Env::Api>>,
+    >(
+        self,
+        user: Arg0,
+    ) -> TxTypedCall<Env, From, To, NotPayable, Gas, BigUint<Env::Api>> {
+        self.wrapped_tx
+            .payment(NotPayable)
+            .raw_call("getDelegatedEnergyForUser")
+            .argument(&user)
+            .original_result()
+    }
+
+    /// The user's own energy, plus the energy delegated to them, 
+    /// minus their own energy if they delegated it to another address 
+    pub fn get_effective_energy_for_user<
+        Arg0: ProxyArg<ManagedAddress<Env::Api>>,
+    >(
+        self,
+        user: Arg0,
+    ) -> TxTypedCall<Env, From, To, NotPayable, Gas, BigUint<Env::Api>> {
+        self.wrapped_tx
+            .payment(NotPayable)
+            .raw_call("getEffectiveEnergyForUser")
+            .argument(&user)
+            .original_result()
+    }
+
+    /// Rebuilds the caller's energy from the LOCKED tokens they send, 
+    /// based on each token's unlock epoch. The tokens are sent back to the caller. 
+    ///  
+    /// The caller is expected to send all their LOCKED tokens, 
+    /// as the energy of any tokens not sent (including legacy tokens) is discarded. 
+    /// Tokens keep the energy multiplier weight of their unlock epoch's bucket, if any. 
+    ///  
+    /// Expected payments: LOCKED tokens 
+    pub fn recompute_energy(
+        self,
+    ) -> TxTypedCall<Env, From, To, (), Gas, ()> {
+        self.wrapped_tx
+            .raw_call("recomputeEnergy")
+            .original_result()
+    }
+
+    /// Registers a SC to be notified through `onEnergyUpdated(user, old_energy, new_energy)` 
+    /// every time a user's energy entry changes. 
+    /// The call is synchronous, so a failing subscriber reverts the whole transaction. 
+    /// Subscribers are enabled by default. 
+    pub fn add_energy_subscriber<
+        Arg0: ProxyArg<ManagedAddress<Env::Api>>,
+        Arg1: ProxyArg<u64>,
+    >(
+        self,
+        sc_address: Arg0,
+        gas_limit: Arg1,
+    ) -> TxTypedCall<Env, From, To, NotPayable, Gas, ()> {
+        self.wrapped_tx
+            .payment(NotPayable)
+            .raw_call("addEnergySubscriber")
+            .argument(&sc_address)
+            .argument(&gas_limit)
+            .original_result()
+    }
+
+    pub fn remove_energy_subscriber<
+        Arg0: ProxyArg<ManagedAddress<Env::Api>>,
+    >(
+        self,
+        sc_address: Arg0,
+    ) -> TxTypedCall<Env, From, To, NotPayable, Gas, ()> {
+        self.wrapped_tx
+            .payment(NotPayable)
+            .raw_call("removeEnergySubscriber")
+            .argument(&sc_address)
+            .original_result()
+    }
+
+    pub fn set_energy_subscriber_enabled<
+        Arg0: ProxyArg<ManagedAddress<Env::Api>>,
+        Arg1: ProxyArg<bool>,
+    >(
+        self,
+        sc_address: Arg0,
+        enabled: Arg1,
+    ) -> TxTypedCall<Env, From, To, NotPayable, Gas, ()> {
+        self.wrapped_tx
+            .payment(NotPayable)
+            .raw_call("setEnergySubscriberEnabled")
+            .argument(&sc_address)
+            .argument(&enabled)
+            .original_result()
+    }
+
+    pub fn set_energy_subscriber_gas_limit<
+        Arg0: ProxyArg<ManagedAddress<Env::Api>>,
+        Arg1: ProxyArg<u64>,
+    >(
+        self,
+        sc_address: Arg0,
+        gas_limit: Arg1,
+    ) -> TxTypedCall<Env, From, To, NotPayable, Gas, ()> {
+        self.wrapped_tx
+            .payment(NotPayable)
+            .raw_call("setEnergySubscriberGasLimit")
+            .argument(&sc_address)
+            .argument(&gas_limit)
+            .original_result()
+    }
+
+    /// Returns pairs of (subscriber address, config) 
+    pub fn get_energy_subscribers(
+        self,
+    ) -> TxTypedCall<Env, From, To, NotPayable, Gas, MultiValueEncoded<Env::Api, MultiValue2<ManagedAddress<Env::Api>, EnergySubscriberConfig>>> {
+        self.wrapped_tx
+            .payment(NotPayable)
+            .raw_call("getEnergySubscribers")
+            .original_result()
+    }
+
+    /// Number of epochs after a lock during which the position can be cancelled penalty-free. 
+    /// 0 disables cancellation. 
+    pub fn set_lock_cancellation_grace_period<
+        Arg0: ProxyArg<u64>,
+    >(
+        self,
+        grace_period: Arg0,
+    ) -> TxTypedCall<Env, From, To, NotPayable, Gas, ()> {
+        self.wrapped_tx
+            .payment(NotPayable)
+            .raw_call("setLockCancellationGracePeriod")
+            .argument(&grace_period)
+            .original_result()
+    }
+
+    /// Cancels a lock within the grace period, returning the base asset without any penalty. 
+    /// The energy gained from the lock is reverted. 
+    ///  
+    /// Only the amount the caller locked from the base asset into the nonce may be cancelled. 
+    /// Locks made for another destination address are not cancellable by either of them. 
+    /// Tokens received otherwise, e.g. by extending, merging or transfers, are not cancellable. 
+    /// Since tokens with the same unlock epoch share the same nonce, 
+    /// further locks into it within the grace period add to the cancellable amount, 
+    /// while the grace period still starts at the first of them. 
+    ///  
+    /// Expected payments: LOCKED tokens 
+    ///  
+    /// Output payments: the originally locked tokens 
+    pub fn cancel_lock(
+        self,
+    ) -> TxTypedCall<Env, From, To, (), Gas, EsdtTokenPayment<Env::Api>> {
+        self.wrapped_tx
+            .raw_call("cancelLock")
+            .original_result()
+    }
+
+    /// The amount of the given nonce the user may still cancel, 0 once the grace period passed 
+    pub fn get_cancellable_amount<
+        Arg0: ProxyArg<ManagedAddress<Env::Api>>,
+        Arg1: ProxyArg<u64>,
+    >(
+        self,
+        user: Arg0,
+        token_nonce: Arg1,
+    ) -> TxTypedCall<Env, From, To, NotPayable, Gas, BigUint<Env::Api>> {
+        self.wrapped_tx
+            .payment(NotPayable)
+            .raw_call("getCancellableAmount")
+            .argument(&user)
+            .argument(&token_nonce)
+            .original_result()
+    }
+
+    pub fn lock_cancellation_grace_period(
+        self,
+    ) -> TxTypedCall<Env, From, To, NotPayable, Gas, u64> {
+        self.wrapped_tx
+            .payment(NotPayable)
+            .raw_call("getLockCancellationGracePeriod")
+            .original_result()
+    }
+
+    pub fn unlock_epoch_rounding_mode(
+        self,
+    ) -> TxTypedCall<Env, From, To, NotPayable, Gas, UnlockEpochRoundingMode> {
+        self.wrapped_tx
+            .payment(NotPayable)
+            .raw_call("getUnlockEpochRoundingMode")
+            .original_result()
+    }
+
+    pub fn lock_range_mode_enabled(
+        self,
+    ) -> TxTypedCall<Env, From, To, NotPayable, Gas, bool> {
+        self.wrapped_tx
+            .payment(NotPayable)
+            .raw_call("isLockRangeModeEnabled")
+            .original_result()
+    }
+
     /// Add lock options, as pairs of epochs and penalty percentages. 
     /// lock epochs must be >= 360 epochs (1 year), 
     /// percentages must be between 0 and 10_000 
@@ -258,18 +591,265 @@ where
             .original_result()
     }
 
+    /// Removes the given lock options. At least one option must remain, 
+    /// and the max option may not go below the min lock period of migrated tokens. 
+    ///  
+    /// Existing tokens locked for longer than the new max option are not affected, 
+    /// except that they incur the max option's penalty on early unlock. 
+    pub fn remove_lock_options<
+        Arg0: ProxyArg<MultiValueEncoded<Env::Api, u64>>,
+    >(
+        self,
+        lock_epochs_to_remove: Arg0,
+    ) -> TxTypedCall<Env, From, To, NotPayable, Gas, ()> {
+        self.wrapped_tx
+            .payment(NotPayable)
+            .raw_call("removeLockOptions")
+            .argument(&lock_epochs_to_remove)
+            .original_result()
+    }
+
+    /// Updates the penalty percentage of a listed lock option. 
+    /// Percentages must still increase as lock period increases. 
+    pub fn update_lock_option_penalty<
+        Arg0: ProxyArg<u64>,
+        Arg1: ProxyArg<u64>,
+    >(
+        self,
+        lock_epochs: Arg0,
+        penalty_start_percentage: Arg1,
+    ) -> TxTypedCall<Env, From, To, NotPayable, Gas, ()> {
+        self.wrapped_tx
+            .payment(NotPayable)
+            .raw_call("updateLockOptionPenalty")
+            .argument(&lock_epochs)
+            .argument(&penalty_start_percentage)
+            .original_result()
+    }
+
+    /// Restricts the epochs during which a lock option may be chosen for new locks. 
+    /// Both ends are inclusive, and either may be left unset. 
+    /// The option is still used for penalty calculation outside its window. 
+    pub fn set_lock_option_availability<
+        Arg0: ProxyArg<u64>,
+        Arg1: ProxyArg<Option<u64>>,
+        Arg2: ProxyArg<Option<u64>>,
+    >(
+        self,
+        lock_epochs: Arg0,
+        available_from_epoch: Arg1,
+        available_until_epoch: Arg2,
+    ) -> TxTypedCall<Env, From, To, NotPayable, Gas, ()> {
+        self.wrapped_tx
+            .payment(NotPayable)
+            .raw_call("setLockOptionAvailability")
+            .argument(&lock_epochs)
+            .argument(&available_from_epoch)
+            .argument(&available_until_epoch)
+            .original_result()
+    }
+
+    /// Schedules a new table of lock options, as pairs of epochs and penalty percentages, 
+    /// which replaces all the current options at `effective_epoch`. 
+    /// The effective epoch must be at least the schedule delay in the future, 
+    /// so users are not surprised by penalty changes. 
+    ///  
+    /// Options must be sorted by lock epochs, with increasing percentages, 
+    /// and follow the same rules as in addLockOptions. Options matching a current option's 
+    /// lock epochs keep its energy multiplier and availability window. 
+    /// Any previously scheduled table is replaced, and other changes made to the current options 
+    /// until the effective epoch are discarded. 
+    pub fn schedule_lock_options<
+        Arg0: ProxyArg<u64>,
+        Arg1: ProxyArg<MultiValueEncoded<Env::Api, MultiValue2<u64, u64>>>,
+    >(
+        self,
+        effective_epoch: Arg0,
+        lock_options: Arg1,
+    ) -> TxTypedCall<Env, From, To, NotPayable, Gas, ()> {
+        self.wrapped_tx
+            .payment(NotPayable)
+            .raw_call("scheduleLockOptions")
+            .argument(&effective_epoch)
+            .argument(&lock_options)
+            .original_result()
+    }
+
+    /// Cancels the scheduled lock options, which must not be active yet 
+    pub fn cancel_scheduled_lock_options(
+        self,
+    ) -> TxTypedCall<Env, From, To, NotPayable, Gas, ()> {
+        self.wrapped_tx
+            .payment(NotPayable)
+            .raw_call("cancelScheduledLockOptions")
+            .original_result()
+    }
+
+    /// Sets the min number of epochs between scheduling lock options and their effective epoch. 
+    /// May not be lower than MIN_LOCK_OPTIONS_SCHEDULE_DELAY, which is also the default. 
+    /// Only applies to tables scheduled afterwards. 
+    pub fn set_lock_options_schedule_delay<
+        Arg0: ProxyArg<u64>,
+    >(
+        self,
+        schedule_delay: Arg0,
+    ) -> TxTypedCall<Env, From, To, NotPayable, Gas, ()> {
+        self.wrapped_tx
+            .payment(NotPayable)
+            .raw_call("setLockOptionsScheduleDelay")
+            .argument(&schedule_delay)
+            .original_result()
+    }
+
+    pub fn get_lock_options_schedule_delay(
+        self,
+    ) -> TxTypedCall<Env, From, To, NotPayable, Gas, u64> {
+        self.wrapped_tx
+            .payment(NotPayable)
+            .raw_call("getLockOptionsScheduleDelay")
+            .original_result()
+    }
+
+    /// Sets the number of epochs unlock epochs are aligned to, e.g. 30 for months or 7 for weeks. 
+    /// Only affects tokens locked afterwards. 
+    pub fn set_unlock_epoch_granularity<
+        Arg0: ProxyArg<u64>,
+    >(
+        self,
+        granularity: Arg0,
+    ) -> TxTypedCall<Env, From, To, NotPayable, Gas, ()> {
+        self.wrapped_tx
+            .payment(NotPayable)
+            .raw_call("setUnlockEpochGranularity")
+            .argument(&granularity)
+            .original_result()
+    }
+
+    pub fn get_unlock_epoch_granularity_view(
+        self,
+    ) -> TxTypedCall<Env, From, To, NotPayable, Gas, u64> {
+        self.wrapped_tx
+            .payment(NotPayable)
+            .raw_call("getUnlockEpochGranularity")
+            .original_result()
+    }
+
+    /// Sets how unlock epochs of new locks are aligned to the granularity: 
+    /// - Floor: round down (default) 
+    /// - Ceil: round up 
+    /// - None: no rounding 
+    pub fn set_unlock_epoch_rounding_mode<
+        Arg0: ProxyArg<UnlockEpochRoundingMode>,
+    >(
+        self,
+        rounding_mode: Arg0,
+    ) -> TxTypedCall<Env, From, To, NotPayable, Gas, ()> {
+        self.wrapped_tx
+            .payment(NotPayable)
+            .raw_call("setUnlockEpochRoundingMode")
+            .argument(&rounding_mode)
+            .original_result()
+    }
+
+    /// In range mode, users may lock for any number of epochs 
+    /// between the shortest and the longest available option, not only the exact options. 
+    /// Penalties are interpolated between the surrounding options. 
+    pub fn set_lock_range_mode<
+        Arg0: ProxyArg<bool>,
+    >(
+        self,
+        enabled: Arg0,
+    ) -> TxTypedCall<Env, From, To, NotPayable, Gas, ()> {
+        self.wrapped_tx
+            .payment(NotPayable)
+            .raw_call("setLockRangeMode")
+            .argument(&enabled)
+            .original_result()
+    }
+
+    /// Sets the energy multiplier for a listed lock option, 
+    /// where 10_000 means 1x, i.e. one energy point per locked token per epoch. 
+    /// For example, a multiplier of 12_000 gives 1.2x energy per epoch. 
+    ///  
+    /// Only affects tokens locked afterwards, existing positions keep their original weight. 
+    pub fn set_lock_option_energy_multiplier<
+        Arg0: ProxyArg<u64>,
+        Arg1: ProxyArg<u64>,
+    >(
+        self,
+        lock_epochs: Arg0,
+        multiplier: Arg1,
+    ) -> TxTypedCall<Env, From, To, NotPayable, Gas, ()> {
+        self.wrapped_tx
+            .payment(NotPayable)
+            .raw_call("setLockOptionEnergyMultiplier")
+            .argument(&lock_epochs)
+            .argument(&multiplier)
+            .original_result()
+    }
+
+    pub fn get_lock_option_energy_multiplier<
+        Arg0: ProxyArg<u64>,
+    >(
+        self,
+        lock_epochs: Arg0,
+    ) -> TxTypedCall<Env, From, To, NotPayable, Gas, u64> {
+        self.wrapped_tx
+            .payment(NotPayable)
+            .raw_call("getLockOptionEnergyMultiplier")
+            .argument(&lock_epochs)
+            .original_result()
+    }
+
     pub fn get_lock_options_view(
         self,
-    ) -> TxTypedCall<Env, From, To, NotPayable, Gas, ArrayVec<LockOption, 10usize>> {
+    ) -> TxTypedCall<Env, From, To, NotPayable, Gas, ArrayVec<LockOption, 120usize>> {
         self.wrapped_tx
             .payment(NotPayable)
             .raw_call("getLockOptions")
             .original_result()
     }
 
-    /// Unlock a locked token instantly. This incures a penalty. 
+    /// Returns the lock options, along with their availability window 
+    /// and whether they may currently be chosen for new locks 
+    pub fn get_lock_options_with_availability(
+        self,
+    ) -> TxTypedCall<Env, From, To, NotPayable, Gas, MultiValueEncoded<Env::Api, MultiValue3<LockOption, LockOptionAvailability, bool>>> {
+        self.wrapped_tx
+            .payment(NotPayable)
+            .raw_call("getLockOptionsWithAvailability")
+            .original_result()
+    }
+
+    /// Returns the epoch at which the scheduled lock options replace the current ones, 
+    /// if a change is pending 
+    pub fn get_scheduled_lock_options_epoch(
+        self,
+    ) -> TxTypedCall<Env, From, To, NotPayable, Gas, Option<u64>> {
+        self.wrapped_tx
+            .payment(NotPayable)
+            .raw_call("getScheduledLockOptionsEpoch")
+            .original_result()
+    }
+
+    /// Returns the pending lock options, if any 
+    pub fn get_scheduled_lock_options_view(
+        self,
+    ) -> TxTypedCall<Env, From, To, NotPayable, Gas, MultiValueEncoded<Env::Api, LockOption>> {
+        self.wrapped_tx
+            .payment(NotPayable)
+            .raw_call("getScheduledLockOptions")
+            .original_result()
+    }
+
+    /// Unlock locked tokens instantly. This incures a penalty. 
     /// The longer the remaining locking time, the bigger the penalty. 
     /// Tokens can be unlocked through another SC after the unbond period has passed. 
+    ///  
+    /// Expected payments: any number of LOCKED tokens, with any nonces. 
+    /// The penalty is computed separately for each of them, 
+    /// and all of them go through a single deposit in the unstake SC. 
+    /// For several nonces, the unstake SC's `depositUserTokens` must accept all of them at once. 
     pub fn unlock_early(
         self,
     ) -> TxTypedCall<Env, From, To, (), Gas, ()> {
@@ -278,17 +858,94 @@ where
             .original_result()
     }
 
-    /// Reduce the locking period of a locked token. This incures a penalty. 
+    /// Reduce the locking period of locked tokens. This incures a penalty. 
     /// The longer the reduction, the bigger the penalty. 
-    /// new_lock_period must be one of the available lock options 
+    /// new_lock_period must be one of the available lock options. 
+    /// The new unlock epoch is rounded like for lockTokens. 
+    ///  
+    /// Expected payments: any number of LOCKED tokens, with any nonces. 
+    /// The penalty is computed separately for each of them. 
+    ///  
+    /// Output payments: a single LOCKED token position, with the new lock period 
     pub fn reduce_lock_period<
         Arg0: ProxyArg<u64>,
     >(
         self,
-        new_lock_period: Arg0,
-    ) -> TxTypedCall<Env, From, To, (), Gas, EsdtTokenPayment<Env::Api>> {
+        new_lock_period: Arg0,
+    ) -> TxTypedCall<Env, From, To, (), Gas, EsdtTokenPayment<Env::Api>> {
+        self.wrapped_tx
+            .raw_call("reduceLockPeriod")
+            .argument(&new_lock_period)
+            .original_result()
+    }
+
+    /// Same as reduceLockPeriod, but the new unlock epoch is given explicitly instead of rounded. 
+    ///  
+    /// Arguments: 
+    /// - target_unlock_epoch - must be aligned to the unlock epoch granularity (a month by default), 
+    ///     and before the current unlock epoch of all the payments. 
+    ///     It must also be reachable through lockTokens, i.e. one of the lock options, 
+    ///     added to the current epoch and rounded, gives this epoch (or the range, in range mode) 
+    /// - max_penalty - the maximum total penalty the caller accepts, in LOCKED tokens 
+    ///  
+    /// Expected payments: any number of LOCKED tokens, with any nonces. 
+    ///  
+    /// Output payments: a single LOCKED token position, unlocking at `target_unlock_epoch` 
+    pub fn reduce_lock_period_to_epoch<
+        Arg0: ProxyArg<u64>,
+        Arg1: ProxyArg<BigUint<Env::Api>>,
+    >(
+        self,
+        target_unlock_epoch: Arg0,
+        max_penalty: Arg1,
+    ) -> TxTypedCall<Env, From, To, (), Gas, EsdtTokenPayment<Env::Api>> {
+        self.wrapped_tx
+            .raw_call("reduceLockPeriodToEpoch")
+            .argument(&target_unlock_epoch)
+            .argument(&max_penalty)
+            .original_result()
+    }
+
+    /// Quotes what unlockEarly would currently return for the given user. 
+    /// The new unlock epoch is the current epoch, before any unbond period. 
+    pub fn get_unlock_early_quote<
+        Arg0: ProxyArg<ManagedAddress<Env::Api>>,
+        Arg1: ProxyArg<u64>,
+        Arg2: ProxyArg<BigUint<Env::Api>>,
+    >(
+        self,
+        user: Arg0,
+        token_nonce: Arg1,
+        amount: Arg2,
+    ) -> TxTypedCall<Env, From, To, NotPayable, Gas, LockReduceQuote<Env::Api>> {
+        self.wrapped_tx
+            .payment(NotPayable)
+            .raw_call("getUnlockEarlyQuote")
+            .argument(&user)
+            .argument(&token_nonce)
+            .argument(&amount)
+            .original_result()
+    }
+
+    /// Quotes what reduceLockPeriod would currently return for the given user 
+    pub fn get_reduce_lock_period_quote<
+        Arg0: ProxyArg<ManagedAddress<Env::Api>>,
+        Arg1: ProxyArg<u64>,
+        Arg2: ProxyArg<BigUint<Env::Api>>,
+        Arg3: ProxyArg<u64>,
+    >(
+        self,
+        user: Arg0,
+        token_nonce: Arg1,
+        amount: Arg2,
+        new_lock_period: Arg3,
+    ) -> TxTypedCall<Env, From, To, NotPayable, Gas, LockReduceQuote<Env::Api>> {
         self.wrapped_tx
-            .raw_call("reduceLockPeriod")
+            .payment(NotPayable)
+            .raw_call("getReduceLockPeriodQuote")
+            .argument(&user)
+            .argument(&token_nonce)
+            .argument(&amount)
             .argument(&new_lock_period)
             .original_result()
     }
@@ -296,15 +953,19 @@ where
     /// Calculates the penalty that would be incurred if `token_amount` tokens 
     /// were to have their lock epochs reduced from `prev_lock_epochs` to 
     /// `new_lock_epochs`. For full unlock, `new_lock_epochs` should be 0. 
+    /// If `opt_user` is provided, the user's penalty discount is applied, 
+    /// as for unlockEarly and reduceLockPeriod with tokens unlocking after `prev_lock_epochs`. 
     pub fn calculate_penalty_amount<
         Arg0: ProxyArg<BigUint<Env::Api>>,
         Arg1: ProxyArg<u64>,
         Arg2: ProxyArg<u64>,
+        Arg3: ProxyArg<OptionalValue<ManagedAddress<Env::Api>>>,
     >(
         self,
         token_amount: Arg0,
         prev_lock_epochs: Arg1,
         new_lock_epochs: Arg2,
+        opt_user: Arg3,
     ) -> TxTypedCall<Env, From, To, NotPayable, Gas, BigUint<Env::Api>> {
         self.wrapped_tx
             .payment(NotPayable)
@@ -312,6 +973,7 @@ where
             .argument(&token_amount)
             .argument(&prev_lock_epochs)
             .argument(&new_lock_epochs)
+            .argument(&opt_user)
             .original_result()
     }
 
@@ -354,6 +1016,95 @@ where
             .original_result()
     }
 
+    /// Switches between depositing early unlocked tokens in the token unstake SC 
+    /// and keeping them in this SC for `unbondEpochs` epochs. 
+    /// Already pending entries can still be claimed or cancelled after switching. 
+    /// In the internal mode, penalties are always handled by this SC, 
+    /// and burned if no penalty distribution is set. 
+    pub fn set_unbond_mode<
+        Arg0: ProxyArg<UnbondMode>,
+    >(
+        self,
+        unbond_mode: Arg0,
+    ) -> TxTypedCall<Env, From, To, NotPayable, Gas, ()> {
+        self.wrapped_tx
+            .payment(NotPayable)
+            .raw_call("setUnbondMode")
+            .argument(&unbond_mode)
+            .original_result()
+    }
+
+    pub fn set_unbond_epochs<
+        Arg0: ProxyArg<u64>,
+    >(
+        self,
+        unbond_epochs: Arg0,
+    ) -> TxTypedCall<Env, From, To, NotPayable, Gas, ()> {
+        self.wrapped_tx
+            .payment(NotPayable)
+            .raw_call("setUnbondEpochs")
+            .argument(&unbond_epochs)
+            .original_result()
+    }
+
+    /// Claims the base asset of all the entries whose unbond period has passed. 
+    /// Their locked tokens are burned. Penalties were already taken out on unlockEarly. 
+    ///  
+    /// Output payments: the unlocked tokens 
+    pub fn claim_unbonded(
+        self,
+    ) -> TxTypedCall<Env, From, To, NotPayable, Gas, EsdtTokenPayment<Env::Api>> {
+        self.wrapped_tx
+            .payment(NotPayable)
+            .raw_call("claimUnbonded")
+            .original_result()
+    }
+
+    /// Cancels all the pending entries, including the claimable ones. 
+    /// The locked tokens are sent back, and their energy is restored. 
+    /// Penalties are not given back. 
+    ///  
+    /// Output payments: the locked tokens 
+    pub fn cancel_unbond(
+        self,
+    ) -> TxTypedCall<Env, From, To, NotPayable, Gas, MultiValueEncoded<Env::Api, EsdtTokenPayment<Env::Api>>> {
+        self.wrapped_tx
+            .payment(NotPayable)
+            .raw_call("cancelUnbond")
+            .original_result()
+    }
+
+    pub fn unbond_mode(
+        self,
+    ) -> TxTypedCall<Env, From, To, NotPayable, Gas, UnbondMode> {
+        self.wrapped_tx
+            .payment(NotPayable)
+            .raw_call("getUnbondMode")
+            .original_result()
+    }
+
+    pub fn unbond_epochs(
+        self,
+    ) -> TxTypedCall<Env, From, To, NotPayable, Gas, u64> {
+        self.wrapped_tx
+            .payment(NotPayable)
+            .raw_call("getUnbondEpochs")
+            .original_result()
+    }
+
+    pub fn unbond_entries<
+        Arg0: ProxyArg<ManagedAddress<Env::Api>>,
+    >(
+        self,
+        user: Arg0,
+    ) -> TxTypedCall<Env, From, To, NotPayable, Gas, MultiValueEncoded<Env::Api, UnbondEntry<Env::Api>>> {
+        self.wrapped_tx
+            .payment(NotPayable)
+            .raw_call("getUnbondEntries")
+            .argument(&user)
+            .original_result()
+    }
+
     /// Sets the energy amounts and token amounts for users. Overwrites any existing values. 
     /// Expects any number of pairs of (user address, token amount, energy amount). 
     pub fn set_energy_for_old_tokens<
@@ -369,6 +1120,22 @@ where
             .original_result()
     }
 
+    /// Adds the energy of users whose entries were saved before the total energy existed. 
+    /// The contract is paused on upgrade until all such users are added. 
+    /// Users already included in the total are skipped. 
+    pub fn add_users_to_total_energy<
+        Arg0: ProxyArg<MultiValueEncoded<Env::Api, ManagedAddress<Env::Api>>>,
+    >(
+        self,
+        users: Arg0,
+    ) -> TxTypedCall<Env, From, To, NotPayable, Gas, ()> {
+        self.wrapped_tx
+            .payment(NotPayable)
+            .raw_call("addUsersToTotalEnergy")
+            .argument(&users)
+            .original_result()
+    }
+
     pub fn update_energy_after_old_token_unlock<
         Arg0: ProxyArg<ManagedAddress<Env::Api>>,
         Arg1: ProxyArg<common_structs::locked_token_types::UnlockEpochAmountPairs<Env::Api>>,
@@ -463,6 +1230,225 @@ where
             .original_result()
     }
 
+    pub fn set_penalty_curve<
+        Arg0: ProxyArg<PenaltyCurve>,
+    >(
+        self,
+        penalty_curve: Arg0,
+    ) -> TxTypedCall<Env, From, To, NotPayable, Gas, ()> {
+        self.wrapped_tx
+            .payment(NotPayable)
+            .raw_call("setPenaltyCurve")
+            .argument(&penalty_curve)
+            .original_result()
+    }
+
+    pub fn penalty_curve(
+        self,
+    ) -> TxTypedCall<Env, From, To, NotPayable, Gas, PenaltyCurve> {
+        self.wrapped_tx
+            .payment(NotPayable)
+            .raw_call("getPenaltyCurve")
+            .original_result()
+    }
+
+    /// Replaces the penalty discount tiers, as pairs of min energy and discount in basis points. 
+    /// Users with at least `min_energy` energy (as in getEnergyAmountForUser) 
+    /// get their early unlock penalties reduced by the tier's discount. 
+    /// Both the thresholds and the discounts must be strictly increasing. 
+    /// An empty list removes all discounts. 
+    pub fn set_penalty_discount_tiers<
+        Arg0: ProxyArg<MultiValueEncoded<Env::Api, MultiValue2<BigUint<Env::Api>, u64>>>,
+    >(
+        self,
+        tiers: Arg0,
+    ) -> TxTypedCall<Env, From, To, NotPayable, Gas, ()> {
+        self.wrapped_tx
+            .payment(NotPayable)
+            .raw_call("setPenaltyDiscountTiers")
+            .argument(&tiers)
+            .original_result()
+    }
+
+    /// Discount in basis points, based on the user's current energy 
+    pub fn get_penalty_discount_for_user<
+        Arg0: ProxyArg<ManagedAddress<Env::Api>>,
+    >(
+        self,
+        user: Arg0,
+    ) -> TxTypedCall<Env, From, To, NotPayable, Gas, u64> {
+        self.wrapped_tx
+            .payment(NotPayable)
+            .raw_call("getPenaltyDiscountForUser")
+            .argument(&user)
+            .original_result()
+    }
+
+    pub fn penalty_discount_tiers(
+        self,
+    ) -> TxTypedCall<Env, From, To, NotPayable, Gas, ManagedVec<Env::Api, PenaltyDiscountTier<Env::Api>>> {
+        self.wrapped_tx
+            .payment(NotPayable)
+            .raw_call("getPenaltyDiscountTiers")
+            .original_result()
+    }
+
+    /// Total penalties collected between the two epochs, both inclusive. 
+    /// Weeks start at epochs divisible by 7. 
+    ///  
+    /// Early unlock penalties are only counted if distributed by this SC, 
+    /// as the unstake SC gives them back when the unbond is cancelled. 
+    pub fn get_penalties_collected<
+        Arg0: ProxyArg<u64>,
+        Arg1: ProxyArg<u64>,
+    >(
+        self,
+        from_epoch: Arg0,
+        to_epoch: Arg1,
+    ) -> TxTypedCall<Env, From, To, NotPayable, Gas, BigUint<Env::Api>> {
+        self.wrapped_tx
+            .payment(NotPayable)
+            .raw_call("getPenaltiesCollected")
+            .argument(&from_epoch)
+            .argument(&to_epoch)
+            .original_result()
+    }
+
+    pub fn total_penalties_collected(
+        self,
+    ) -> TxTypedCall<Env, From, To, NotPayable, Gas, BigUint<Env::Api>> {
+        self.wrapped_tx
+            .payment(NotPayable)
+            .raw_call("getTotalPenaltiesCollected")
+            .original_result()
+    }
+
+    pub fn penalties_collected_for_epoch<
+        Arg0: ProxyArg<u64>,
+    >(
+        self,
+        epoch: Arg0,
+    ) -> TxTypedCall<Env, From, To, NotPayable, Gas, BigUint<Env::Api>> {
+        self.wrapped_tx
+            .payment(NotPayable)
+            .raw_call("getPenaltiesCollectedForEpoch")
+            .argument(&epoch)
+            .original_result()
+    }
+
+    pub fn penalties_collected_for_week<
+        Arg0: ProxyArg<u64>,
+    >(
+        self,
+        week: Arg0,
+    ) -> TxTypedCall<Env, From, To, NotPayable, Gas, BigUint<Env::Api>> {
+        self.wrapped_tx
+            .payment(NotPayable)
+            .raw_call("getPenaltiesCollectedForWeek")
+            .argument(&week)
+            .original_result()
+    }
+
+    /// Splits penalties, in basis points, between burning the base asset, 
+    /// the fees collector (through depositSwapFees) and the treasury. 
+    /// While not set, penalties are handled by the unstake SC, 
+    /// or burned if the internal unbond mode is set. 
+    pub fn set_penalty_distribution<
+        Arg0: ProxyArg<u64>,
+        Arg1: ProxyArg<u64>,
+        Arg2: ProxyArg<u64>,
+    >(
+        self,
+        burn_shares: Arg0,
+        fees_collector_shares: Arg1,
+        treasury_shares: Arg2,
+    ) -> TxTypedCall<Env, From, To, NotPayable, Gas, ()> {
+        self.wrapped_tx
+            .payment(NotPayable)
+            .raw_call("setPenaltyDistribution")
+            .argument(&burn_shares)
+            .argument(&fees_collector_shares)
+            .argument(&treasury_shares)
+            .original_result()
+    }
+
+    /// Penalties go back to being handled by the unstake SC, or burned in the internal unbond mode 
+    pub fn clear_penalty_distribution(
+        self,
+    ) -> TxTypedCall<Env, From, To, NotPayable, Gas, ()> {
+        self.wrapped_tx
+            .payment(NotPayable)
+            .raw_call("clearPenaltyDistribution")
+            .original_result()
+    }
+
+    pub fn set_fees_collector_address<
+        Arg0: ProxyArg<ManagedAddress<Env::Api>>,
+    >(
+        self,
+        sc_address: Arg0,
+    ) -> TxTypedCall<Env, From, To, NotPayable, Gas, ()> {
+        self.wrapped_tx
+            .payment(NotPayable)
+            .raw_call("setFeesCollectorAddress")
+            .argument(&sc_address)
+            .original_result()
+    }
+
+    pub fn set_treasury_address<
+        Arg0: ProxyArg<ManagedAddress<Env::Api>>,
+    >(
+        self,
+        address: Arg0,
+    ) -> TxTypedCall<Env, From, To, NotPayable, Gas, ()> {
+        self.wrapped_tx
+            .payment(NotPayable)
+            .raw_call("setTreasuryAddress")
+            .argument(&address)
+            .original_result()
+    }
+
+    pub fn penalty_distribution(
+        self,
+    ) -> TxTypedCall<Env, From, To, NotPayable, Gas, PenaltyDistribution> {
+        self.wrapped_tx
+            .payment(NotPayable)
+            .raw_call("getPenaltyDistribution")
+            .original_result()
+    }
+
+    pub fn fees_collector_address(
+        self,
+    ) -> TxTypedCall<Env, From, To, NotPayable, Gas, ManagedAddress<Env::Api>> {
+        self.wrapped_tx
+            .payment(NotPayable)
+            .raw_call("getFeesCollectorAddress")
+            .original_result()
+    }
+
+    pub fn treasury_address(
+        self,
+    ) -> TxTypedCall<Env, From, To, NotPayable, Gas, ManagedAddress<Env::Api>> {
+        self.wrapped_tx
+            .payment(NotPayable)
+            .raw_call("getTreasuryAddress")
+            .original_result()
+    }
+
+    /// Total base asset amount burned or sent to the given destination 
+    pub fn total_penalty_distributed<
+        Arg0: ProxyArg<PenaltyDestination>,
+    >(
+        self,
+        destination: Arg0,
+    ) -> TxTypedCall<Env, From, To, NotPayable, Gas, BigUint<Env::Api>> {
+        self.wrapped_tx
+            .payment(NotPayable)
+            .raw_call("getTotalPenaltyDistributed")
+            .argument(&destination)
+            .original_result()
+    }
+
     pub fn lock_virtual<
         Arg0: ProxyArg<TokenIdentifier<Env::Api>>,
         Arg1: ProxyArg<BigUint<Env::Api>>,
@@ -582,12 +1568,106 @@ where
 }
 
 #[type_abi]
-#[derive(TopEncode, TopDecode)]
+#[derive(TopEncode, TopDecode, NestedEncode, NestedDecode, Clone, Copy, PartialEq, Debug)]
+pub enum EnergyDecayMode {
+    Continuous,
+    Weekly,
+}
+
+#[type_abi]
+#[derive(TopEncode, TopDecode, NestedEncode, NestedDecode)]
+pub struct EnergySubscriberConfig {
+    pub enabled: bool,
+    pub gas_limit: u64,
+}
+
+#[type_abi]
+#[derive(TopEncode, TopDecode, NestedEncode, NestedDecode, Clone, PartialEq, Debug, Default)]
+pub struct PenaltyDistribution {
+    pub burn_shares: u64,
+    pub fees_collector_shares: u64,
+    pub treasury_shares: u64,
+}
+
+#[type_abi]
+#[derive(TopEncode, TopDecode, NestedEncode, NestedDecode, Clone, Copy, PartialEq, Debug)]
+pub enum PenaltyDestination {
+    Burn,
+    FeesCollector,
+    Treasury,
+}
+
+#[type_abi]
+#[derive(TopEncode, TopDecode, NestedEncode, NestedDecode, Clone, PartialEq, Debug)]
+pub struct LockReduceQuote<Api>
+where
+    Api: ManagedTypeApi,
+{
+    pub penalty_amount: BigUint<Api>,
+    pub received_amount: BigUint<Api>,
+    pub new_unlock_epoch: u64,
+    pub energy_delta: BigInt<Api>,
+}
+
+#[type_abi]
+#[derive(TopEncode, TopDecode, NestedEncode, NestedDecode, Clone, Copy, PartialEq, Debug)]
+pub enum UnbondMode {
+    ExternalSc,
+    Internal,
+}
+
+#[type_abi]
+#[derive(TopEncode, TopDecode, NestedEncode, NestedDecode, Clone, PartialEq, Debug)]
+pub struct UnbondEntry<Api>
+where
+    Api: ManagedTypeApi,
+{
+    pub unlock_epoch: u64,
+    pub locked_tokens: EsdtTokenPayment<Api>,
+    pub unlocked_amount: BigUint<Api>,
+    pub weighted_amount: BigUint<Api>,
+}
+
+#[type_abi]
+#[derive(TopEncode, TopDecode, NestedEncode, NestedDecode, ManagedVecItem, Clone, PartialEq, Debug)]
+pub struct PenaltyDiscountTier<Api>
+where
+    Api: ManagedTypeApi,
+{
+    pub min_energy: BigUint<Api>,
+    pub discount: u64,
+}
+
+#[type_abi]
+#[derive(TopEncode, TopDecode, NestedEncode, NestedDecode, Clone, Copy, PartialEq, Debug)]
+pub enum PenaltyCurve {
+    Linear,
+    Quadratic,
+    Exponential,
+}
+
+#[type_abi]
+#[derive(TopEncode, TopDecode, NestedEncode, NestedDecode, Clone, Copy, PartialEq, Debug)]
+pub enum UnlockEpochRoundingMode {
+    Floor,
+    Ceil,
+    None,
+}
+
+#[type_abi]
+#[derive(TopEncode, TopDecode, NestedEncode, NestedDecode)]
 pub struct LockOption {
     pub lock_epochs: u64,
     pub penalty_start_percentage: u64,
 }
 
+#[type_abi]
+#[derive(TopEncode, TopDecode, NestedEncode, NestedDecode, Clone, Copy, Default, PartialEq, Debug)]
+pub struct LockOptionAvailability {
+    pub available_from_epoch: Option<u64>,
+    pub available_until_epoch: Option<u64>,
+}
+
 #[type_abi]
 #[derive(TopEncode, TopDecode)]
 pub struct EnergyUpdatedEvent<Api>
@@ -3,6 +3,8 @@ multiversx_sc::derive_imports!();
 
 use common_structs::Epoch;
 
+pub const MAX_USERS_PER_ENERGY_QUERY: usize = 100;
+
 #[derive(TypeAbi, TopEncode, TopDecode, NestedEncode, NestedDecode, Clone, PartialEq, Debug)]
 pub struct Energy<M: ManagedTypeApi> {
     amount: BigInt<M>,
@@ -58,6 +60,9 @@ impl<M: ManagedTypeApi> Energy<M> {
         self.amount -= BigInt::from(energy_decrease);
     }
 
+    /// Depletes all the locked tokens until they're unlocked.
+    /// The energy factory depletes its entries through `UserEnergy::deplete` instead,
+    /// which stops depleting tokens at their unlock epoch.
     pub fn deplete(&mut self, current_epoch: Epoch) {
         if self.last_update_epoch == current_epoch {
             return;
@@ -152,6 +157,8 @@ impl<M: ManagedTypeApi> Energy<M> {
         &self.total_locked_tokens
     }
 
+    /// Only untracked tokens can bring the energy below zero,
+    /// as they keep depleting energy after their unlock epoch.
     pub fn get_energy_amount(&self) -> BigUint<M> {
         if self.amount > 0 {
             self.amount.magnitude()
@@ -166,47 +173,1019 @@ impl<M: ManagedTypeApi> Energy<M> {
     }
 }
 
+/// A user's locked tokens that unlock at the same epoch.
+/// The weighted amount is the energy depleted per epoch by the bucket's tokens.
+#[derive(
+    TypeAbi,
+    ManagedVecItem,
+    TopEncode,
+    TopDecode,
+    NestedEncode,
+    NestedDecode,
+    Clone,
+    PartialEq,
+    Debug,
+)]
+pub struct UnlockBucket<M: ManagedTypeApi> {
+    pub unlock_epoch: Epoch,
+    pub amount: BigUint<M>,
+    pub weighted_amount: BigUint<M>,
+}
+
+pub type UnlockSchedule<M> = ManagedVec<M, UnlockBucket<M>>;
+
+/// The part of an aggregate's depletion rate that stops at the given epoch.
+/// An entry that would go below zero stops depleting the aggregate at the first epoch it does,
+/// and the energy depleted below zero during that epoch is given back.
+#[derive(
+    TypeAbi,
+    ManagedVecItem,
+    TopEncode,
+    TopDecode,
+    NestedEncode,
+    NestedDecode,
+    Clone,
+    PartialEq,
+    Debug,
+)]
+pub struct DepletionRateEnd<M: ManagedTypeApi> {
+    pub epoch: Epoch,
+    pub depletion_rate: BigUint<M>,
+    pub energy_refund: BigUint<M>,
+}
+
+/// A user's energy entry, together with the unlock schedule of their locked tokens.
+/// The entry and the schedule are saved separately, under `userEnergy` and `userUnlockBucket`,
+/// so the entry keeps the format other contracts expect.
+#[derive(TypeAbi, TopEncode, TopDecode, NestedEncode, NestedDecode, Clone, PartialEq, Debug)]
+pub struct UserEnergy<M: ManagedTypeApi> {
+    energy: Energy<M>,
+    unlock_schedule: UnlockSchedule<M>,
+}
+
+impl<M: ManagedTypeApi> UserEnergy<M> {
+    #[inline]
+    pub fn new(energy: Energy<M>, unlock_schedule: UnlockSchedule<M>) -> Self {
+        UserEnergy {
+            energy,
+            unlock_schedule,
+        }
+    }
+
+    pub fn new_zero_energy(current_epoch: Epoch) -> Self {
+        Self::new(Energy::new_zero_energy(current_epoch), ManagedVec::new())
+    }
+
+    /// Tokens in the unlock schedule only deplete energy until their unlock epoch,
+    /// at their weighted amount per epoch.
+    /// Untracked tokens keep depleting energy until they're unlocked.
+    /// Entries are never moved back to an earlier epoch.
+    pub fn deplete(&mut self, current_epoch: Epoch) {
+        let last_update_epoch = self.energy.last_update_epoch;
+        if last_update_epoch >= current_epoch {
+            return;
+        }
+
+        let untracked_tokens = self.get_untracked_locked_tokens();
+        if untracked_tokens > 0 {
+            self.energy
+                .subtract(last_update_epoch, current_epoch, &untracked_tokens);
+        }
+
+        let mut energy_decrease = BigUint::zero();
+        for bucket in self.unlock_schedule.iter() {
+            let depletion_end_epoch = core::cmp::min(bucket.unlock_epoch, current_epoch);
+            if depletion_end_epoch > last_update_epoch {
+                energy_decrease +=
+                    &bucket.weighted_amount * (depletion_end_epoch - last_update_epoch);
+            }
+        }
+        self.energy.amount -= BigInt::from(energy_decrease);
+
+        self.energy.last_update_epoch = current_epoch;
+    }
+
+    /// The energy currently depleted per epoch,
+    /// i.e. by the untracked tokens and by the buckets not yet unlocked
+    pub fn get_depletion_rate(&self) -> BigUint<M> {
+        let mut depletion_rate = self.get_untracked_locked_tokens();
+        for bucket in self.unlock_schedule.iter() {
+            if bucket.unlock_epoch > self.energy.last_update_epoch {
+                depletion_rate += &bucket.weighted_amount;
+            }
+        }
+
+        depletion_rate
+    }
+
+    /// The depletion rate counted in aggregates, which only include the energy above zero
+    pub fn get_aggregated_depletion_rate(&self) -> BigUint<M> {
+        if self.energy.amount > 0 {
+            self.get_depletion_rate()
+        } else {
+            BigUint::zero()
+        }
+    }
+
+    /// The epochs at which the aggregates stop being depleted by this entry.
+    /// Buckets stop at their unlock epoch, unless the energy reaches zero first,
+    /// in which case the whole remaining rate stops at that epoch.
+    pub fn get_depletion_rate_ends(&self) -> ManagedVec<M, DepletionRateEnd<M>> {
+        let mut rate_ends = ManagedVec::new();
+        if self.energy.amount <= 0 {
+            return rate_ends;
+        }
+
+        let last_update_epoch = self.energy.last_update_epoch;
+        let mut energy_amount = self.energy.amount.magnitude();
+        let mut depletion_rate = self.get_depletion_rate();
+
+        // usual case, where only the buckets deplete energy and it never reaches zero
+        let mut pending_energy_decrease = BigUint::zero();
+        for bucket in self.unlock_schedule.iter() {
+            if bucket.unlock_epoch > last_update_epoch {
+                pending_energy_decrease +=
+                    &bucket.weighted_amount * (bucket.unlock_epoch - last_update_epoch);
+            }
+        }
+        if self.get_untracked_locked_tokens() == 0 && pending_energy_decrease <= energy_amount {
+            for bucket in self.unlock_schedule.iter() {
+                if bucket.unlock_epoch > last_update_epoch {
+                    rate_ends.push(DepletionRateEnd {
+                        epoch: bucket.unlock_epoch,
+                        depletion_rate: bucket.weighted_amount,
+                        energy_refund: BigUint::zero(),
+                    });
+                }
+            }
+
+            return rate_ends;
+        }
+
+        // otherwise, the buckets are visited in unlock order until the energy reaches zero
+        let mut epoch = last_update_epoch;
+        while let Some(bucket) = self.find_next_unlock_bucket(epoch) {
+            let energy_decrease = &depletion_rate * (bucket.unlock_epoch - epoch);
+            if energy_decrease >= energy_amount {
+                break;
+            }
+
+            energy_amount -= energy_decrease;
+            depletion_rate -= &bucket.weighted_amount;
+            epoch = bucket.unlock_epoch;
+            rate_ends.push(DepletionRateEnd {
+                epoch,
+                depletion_rate: bucket.weighted_amount,
+                energy_refund: BigUint::zero(),
+            });
+        }
+        if depletion_rate == 0 {
+            return rate_ends;
+        }
+
+        // rounded up, as energy is depleted for whole epochs
+        let epochs_until_zero = (&energy_amount + &depletion_rate - 1u32) / &depletion_rate;
+        let opt_end_epoch = epochs_until_zero
+            .to_u64()
+            .and_then(|epochs| epoch.checked_add(epochs));
+        if let Some(end_epoch) = opt_end_epoch {
+            let energy_refund = &depletion_rate * &epochs_until_zero - &energy_amount;
+            rate_ends.push(DepletionRateEnd {
+                epoch: end_epoch,
+                depletion_rate,
+                energy_refund,
+            });
+        }
+
+        rate_ends
+    }
+
+    /// The bucket with the earliest unlock epoch after the given one
+    fn find_next_unlock_bucket(&self, epoch: Epoch) -> Option<UnlockBucket<M>> {
+        let mut opt_next_bucket: Option<UnlockBucket<M>> = None;
+        for bucket in self.unlock_schedule.iter() {
+            if bucket.unlock_epoch <= epoch {
+                continue;
+            }
+
+            let is_earlier = match &opt_next_bucket {
+                Some(next_bucket) => bucket.unlock_epoch < next_bucket.unlock_epoch,
+                None => true,
+            };
+            if is_earlier {
+                opt_next_bucket = Some(bucket);
+            }
+        }
+
+        opt_next_bucket
+    }
+
+    fn add_to_unlock_schedule(
+        &mut self,
+        unlock_epoch: Epoch,
+        amount: &BigUint<M>,
+        weighted_amount: &BigUint<M>,
+    ) {
+        match self.find_unlock_bucket_index(unlock_epoch) {
+            Some(index) => {
+                let mut bucket = self.unlock_schedule.get(index);
+                bucket.amount += amount;
+                bucket.weighted_amount += weighted_amount;
+                let _ = self.unlock_schedule.set(index, &bucket);
+            }
+            None => self.unlock_schedule.push(UnlockBucket {
+                unlock_epoch,
+                amount: amount.clone(),
+                weighted_amount: weighted_amount.clone(),
+            }),
+        }
+    }
+
+    /// Returns the amount and weighted amount of the unlocked tokens, as found in the unlock schedule.
+    /// Tokens locked with different multipliers may share a bucket,
+    /// so the weighted amount is removed proportionally to the amount.
+    ///
+    /// Legacy tokens share their nonce with the tokens locked later for the same unlock epoch,
+    /// so only up to the bucket's amount is tracked. The rest of the tokens are untracked.
+    fn take_from_unlock_schedule(
+        &mut self,
+        unlock_epoch: Epoch,
+        amount: &BigUint<M>,
+    ) -> (BigUint<M>, BigUint<M>) {
+        let index = match self.find_unlock_bucket_index(unlock_epoch) {
+            Some(index) => index,
+            None => return (BigUint::zero(), BigUint::zero()),
+        };
+
+        let mut bucket = self.unlock_schedule.get(index);
+        if bucket.amount > *amount {
+            let weighted_amount = &bucket.weighted_amount * amount / &bucket.amount;
+            bucket.amount -= amount;
+            bucket.weighted_amount -= &weighted_amount;
+            let _ = self.unlock_schedule.set(index, &bucket);
+
+            (amount.clone(), weighted_amount)
+        } else {
+            self.unlock_schedule.remove(index);
+
+            (bucket.amount, bucket.weighted_amount)
+        }
+    }
+
+    pub fn get_unlock_bucket(&self, unlock_epoch: Epoch) -> Option<UnlockBucket<M>> {
+        self.find_unlock_bucket_index(unlock_epoch)
+            .map(|index| self.unlock_schedule.get(index))
+    }
+
+    /// Removes the given tokens from their unlock epoch's bucket and returns their weighted amount,
+    /// based on the bucket's average weight. Tokens over the bucket's amount are weighted 1x,
+    /// so tokens received from other users do not get the bucket's weight.
+    pub fn take_weighted_amount(&mut self, unlock_epoch: Epoch, amount: &BigUint<M>) -> BigUint<M> {
+        let (tracked_amount, tracked_weighted_amount) =
+            self.take_from_unlock_schedule(unlock_epoch, amount);
+
+        tracked_weighted_amount + (amount - &tracked_amount)
+    }
+
+    /// Only searches the user's own buckets, i.e. one per distinct unlock epoch of their tokens
+    fn find_unlock_bucket_index(&self, unlock_epoch: Epoch) -> Option<usize> {
+        self.unlock_schedule
+            .iter()
+            .position(|bucket| bucket.unlock_epoch == unlock_epoch)
+    }
+
+    pub fn add_after_token_lock(
+        &mut self,
+        lock_amount: &BigUint<M>,
+        unlock_epoch: Epoch,
+        current_epoch: Epoch,
+    ) {
+        self.add_after_weighted_token_lock(lock_amount, lock_amount, unlock_epoch, current_epoch);
+    }
+
+    /// The weighted amount is the energy gained per epoch of lock,
+    /// i.e. the lock amount with the lock option's energy multiplier applied
+    pub fn add_after_weighted_token_lock(
+        &mut self,
+        lock_amount: &BigUint<M>,
+        weighted_amount: &BigUint<M>,
+        unlock_epoch: Epoch,
+        current_epoch: Epoch,
+    ) {
+        self.energy
+            .add(unlock_epoch, current_epoch, weighted_amount);
+        self.energy.total_locked_tokens += lock_amount;
+        self.add_to_unlock_schedule(unlock_epoch, lock_amount, weighted_amount);
+    }
+
+    /// Returns the weighted amount of the unlocked tokens
+    pub fn refund_after_token_unlock(
+        &mut self,
+        unlock_amount: &BigUint<M>,
+        unlock_epoch: Epoch,
+        current_epoch: Epoch,
+    ) -> BigUint<M> {
+        // tokens from the unlock schedule stopped depleting energy at their unlock epoch
+        let (tracked_amount, tracked_weighted_amount) =
+            self.take_from_unlock_schedule(unlock_epoch, unlock_amount);
+        let untracked_amount = unlock_amount - &tracked_amount;
+        self.energy
+            .refund_after_token_unlock(&untracked_amount, unlock_epoch, current_epoch);
+        self.energy.total_locked_tokens -= &tracked_amount;
+
+        tracked_weighted_amount + untracked_amount
+    }
+
+    /// Returns the weighted amount of the unlocked tokens
+    pub fn deplete_after_early_unlock(
+        &mut self,
+        unlock_amount: &BigUint<M>,
+        unlock_epoch: Epoch,
+        current_epoch: Epoch,
+    ) -> BigUint<M> {
+        let (tracked_amount, tracked_weighted_amount) =
+            self.take_from_unlock_schedule(unlock_epoch, unlock_amount);
+        let weighted_amount = tracked_weighted_amount + (unlock_amount - &tracked_amount);
+        self.energy
+            .subtract(current_epoch, unlock_epoch, &weighted_amount);
+        self.energy.total_locked_tokens -= unlock_amount;
+
+        weighted_amount
+    }
+
+    /// Returns the weighted amount of the unlocked tokens
+    pub fn update_after_unlock_any(
+        &mut self,
+        unlock_amount: &BigUint<M>,
+        unlock_epoch: Epoch,
+        current_epoch: Epoch,
+    ) -> BigUint<M> {
+        if unlock_epoch < current_epoch {
+            self.refund_after_token_unlock(unlock_amount, unlock_epoch, current_epoch)
+        } else {
+            self.deplete_after_early_unlock(unlock_amount, unlock_epoch, current_epoch)
+        }
+    }
+
+    /// The tokens keep their original weight
+    pub fn update_after_unlock_epoch_change(
+        &mut self,
+        token_amount: &BigUint<M>,
+        old_unlock_epoch: Epoch,
+        new_unlock_epoch: Epoch,
+        current_epoch: Epoch,
+    ) {
+        let weighted_amount =
+            self.update_after_unlock_any(token_amount, old_unlock_epoch, current_epoch);
+        self.add_after_weighted_token_lock(
+            token_amount,
+            &weighted_amount,
+            new_unlock_epoch,
+            current_epoch,
+        );
+    }
+
+    #[inline]
+    pub fn get_energy_entry(&self) -> &Energy<M> {
+        &self.energy
+    }
+
+    #[inline]
+    pub fn into_energy_entry(self) -> Energy<M> {
+        self.energy
+    }
+
+    #[inline]
+    pub fn get_unlock_schedule(&self) -> &UnlockSchedule<M> {
+        &self.unlock_schedule
+    }
+
+    #[inline]
+    pub fn get_last_update_epoch(&self) -> Epoch {
+        self.energy.last_update_epoch
+    }
+
+    #[inline]
+    pub fn get_total_locked_tokens(&self) -> &BigUint<M> {
+        &self.energy.total_locked_tokens
+    }
+
+    /// Locked tokens not found in the unlock schedule,
+    /// i.e. locked before the schedule was introduced.
+    pub fn get_untracked_locked_tokens(&self) -> BigUint<M> {
+        let tracked_tokens = self.get_tracked_locked_tokens();
+        if self.energy.total_locked_tokens > tracked_tokens {
+            &self.energy.total_locked_tokens - &tracked_tokens
+        } else {
+            BigUint::zero()
+        }
+    }
+
+    fn get_tracked_locked_tokens(&self) -> BigUint<M> {
+        let mut tracked_tokens = BigUint::zero();
+        for bucket in self.unlock_schedule.iter() {
+            tracked_tokens += &bucket.amount;
+        }
+
+        tracked_tokens
+    }
+
+    /// Used for entries computed elsewhere, e.g. after a locked token transfer,
+    /// which may have less locked tokens than the unlock schedule.
+    /// The buckets unlocking last are trimmed first, so the remaining buckets
+    /// do not deplete more energy than the entry was computed with.
+    pub fn trim_unlock_schedule(&mut self) {
+        let tracked_tokens = self.get_tracked_locked_tokens();
+        if tracked_tokens <= self.energy.total_locked_tokens {
+            return;
+        }
+
+        let mut excess_tokens = tracked_tokens - &self.energy.total_locked_tokens;
+        while excess_tokens > 0 {
+            let index = match self.find_last_unlock_bucket_index() {
+                Some(index) => index,
+                None => break,
+            };
+
+            let mut bucket = self.unlock_schedule.get(index);
+            if bucket.amount > excess_tokens {
+                let weighted_amount = &bucket.weighted_amount * &excess_tokens / &bucket.amount;
+                bucket.amount -= &excess_tokens;
+                bucket.weighted_amount -= weighted_amount;
+                let _ = self.unlock_schedule.set(index, &bucket);
+
+                break;
+            }
+
+            excess_tokens -= &bucket.amount;
+            self.unlock_schedule.remove(index);
+        }
+    }
+
+    fn find_last_unlock_bucket_index(&self) -> Option<usize> {
+        let mut opt_last_index: Option<usize> = None;
+        let mut last_unlock_epoch = 0;
+        for (index, bucket) in self.unlock_schedule.iter().enumerate() {
+            if opt_last_index.is_none() || bucket.unlock_epoch > last_unlock_epoch {
+                opt_last_index = Some(index);
+                last_unlock_epoch = bucket.unlock_epoch;
+            }
+        }
+
+        opt_last_index
+    }
+
+    #[inline]
+    pub fn get_energy_amount(&self) -> BigUint<M> {
+        self.energy.get_energy_amount()
+    }
+}
+
+/// The sum of multiple users' energy entries, each counted as its energy amount, clamped at zero.
+/// As the entries stop depleting at different epochs, the aggregate keeps its current depletion rate,
+/// while the part of the rate ending at each epoch is saved separately, keyed by epoch.
+///
+/// Entries saved before the aggregate existed are only included once added
+/// through `addUsersToTotalEnergy`, or on their next update, so removing an entry saturates at zero.
+#[derive(TypeAbi, TopEncode, TopDecode, NestedEncode, NestedDecode, Clone, PartialEq, Debug)]
+pub struct AggregatedEnergy<M: ManagedTypeApi> {
+    energy: Energy<M>,
+    depletion_rate: BigUint<M>,
+}
+
+impl<M: ManagedTypeApi> AggregatedEnergy<M> {
+    #[inline]
+    pub fn new(energy: Energy<M>, depletion_rate: BigUint<M>) -> Self {
+        AggregatedEnergy {
+            energy,
+            depletion_rate,
+        }
+    }
+
+    pub fn new_zero_energy(current_epoch: Epoch) -> Self {
+        Self::new(Energy::new_zero_energy(current_epoch), BigUint::zero())
+    }
+
+    /// Reads the depletion rate ending at each epoch since the last update,
+    /// along with the energy given back at that epoch,
+    /// so the cost only grows with the number of epochs since then.
+    pub fn deplete<F: Fn(Epoch) -> (BigUint<M>, BigUint<M>)>(
+        &mut self,
+        current_epoch: Epoch,
+        get_depletion_rate_end: F,
+    ) {
+        let mut epoch = self.energy.last_update_epoch;
+        if epoch >= current_epoch {
+            return;
+        }
+
+        let mut energy_decrease = BigUint::zero();
+        let mut energy_refund = BigUint::zero();
+        while epoch < current_epoch {
+            energy_decrease += &self.depletion_rate;
+            epoch += 1;
+
+            let (depletion_rate_end, epoch_energy_refund) = get_depletion_rate_end(epoch);
+            self.depletion_rate = saturating_sub(&self.depletion_rate, &depletion_rate_end);
+            energy_refund += epoch_energy_refund;
+        }
+        let energy_amount = self.get_energy_amount() + energy_refund;
+        self.energy.amount = BigInt::from(saturating_sub(&energy_amount, &energy_decrease));
+
+        self.energy.last_update_epoch = current_epoch;
+    }
+
+    /// Both entries are expected to be depleted up to the same epoch
+    pub fn add_user_energy(&mut self, user_energy: &UserEnergy<M>) {
+        self.energy.amount += BigInt::from(user_energy.get_energy_amount());
+        self.energy.total_locked_tokens += user_energy.get_total_locked_tokens();
+        self.depletion_rate += user_energy.get_aggregated_depletion_rate();
+    }
+
+    /// Both entries are expected to be depleted up to the same epoch
+    pub fn remove_user_energy(&mut self, user_energy: &UserEnergy<M>) {
+        let energy_amount =
+            saturating_sub(&self.get_energy_amount(), &user_energy.get_energy_amount());
+        self.energy.amount = BigInt::from(energy_amount);
+        self.energy.total_locked_tokens = saturating_sub(
+            &self.energy.total_locked_tokens,
+            user_energy.get_total_locked_tokens(),
+        );
+        self.depletion_rate = saturating_sub(
+            &self.depletion_rate,
+            &user_energy.get_aggregated_depletion_rate(),
+        );
+    }
+
+    #[inline]
+    pub fn get_energy_entry(&self) -> &Energy<M> {
+        &self.energy
+    }
+
+    #[inline]
+    pub fn get_depletion_rate(&self) -> &BigUint<M> {
+        &self.depletion_rate
+    }
+
+    #[inline]
+    pub fn get_total_locked_tokens(&self) -> &BigUint<M> {
+        &self.energy.total_locked_tokens
+    }
+
+    #[inline]
+    pub fn get_energy_amount(&self) -> BigUint<M> {
+        self.energy.get_energy_amount()
+    }
+}
+
 #[multiversx_sc::module]
-pub trait EnergyModule: crate::events::EventsModule {
-    fn update_energy<T, F: FnOnce(&mut Energy<Self::Api>) -> T>(
+pub trait EnergyModule:
+    crate::events::EventsModule
+    + crate::energy_checkpoint::EnergyCheckpointModule
+    + crate::energy_decay::EnergyDecayModule
+    + crate::energy_subscribers::EnergySubscribersModule
+{
+    fn update_energy<T, F: FnOnce(&mut UserEnergy<Self::Api>) -> T>(
         &self,
         user: &ManagedAddress,
         update_fn: F,
     ) -> T {
-        let mut energy = self.get_updated_energy_entry_for_user(user);
+        let mut energy = self.get_updated_user_energy(user);
         let result = update_fn(&mut energy);
-        self.set_energy_entry(user, energy);
+        self.set_user_energy(user, energy);
 
         result
     }
 
+    /// Used for entries computed by other contracts, which are not aware of the unlock schedule.
+    /// The user's unlock schedule is reconciled with the entry's locked tokens.
     fn set_energy_entry(&self, user: &ManagedAddress, new_energy: Energy<Self::Api>) {
-        let prev_energy = self.get_updated_energy_entry_for_user(user);
-        self.user_energy(user).set(&new_energy);
-        self.emit_energy_updated_event(user, prev_energy, new_energy);
+        let mut energy = UserEnergy::new(new_energy, self.get_user_unlock_schedule(user));
+        energy.trim_unlock_schedule();
+        self.set_user_energy(user, energy);
+    }
+
+    fn set_user_energy(&self, user: &ManagedAddress, new_energy: UserEnergy<Self::Api>) {
+        let prev_energy = self.get_updated_user_energy(user);
+        let energy_epoch = self.get_energy_epoch();
+        let mut new_energy_depleted = new_energy.clone();
+        new_energy_depleted.deplete(energy_epoch);
+
+        let is_legacy_entry = self.is_legacy_energy_entry(user);
+        if is_legacy_entry {
+            let zero_energy = UserEnergy::new_zero_energy(energy_epoch);
+            self.update_total_energy(&zero_energy, &new_energy_depleted);
+        } else {
+            self.update_total_energy(&prev_energy, &new_energy_depleted);
+        }
+
+        let delegatee_mapper = self.energy_delegatee(user);
+        if !delegatee_mapper.is_empty() {
+            let delegatee = delegatee_mapper.get();
+            self.update_delegated_energy(&delegatee, &prev_energy, &new_energy_depleted);
+        }
+
+        // entries saved before checkpoints existed are kept as the first checkpoint
+        let energy_mapper = self.user_energy(user);
+        if is_legacy_entry {
+            self.add_legacy_energy_checkpoint(user);
+        }
+        self.add_energy_checkpoint(user, &new_energy_depleted);
+
+        self.save_user_unlock_schedule(
+            user,
+            prev_energy.get_unlock_schedule(),
+            new_energy.get_unlock_schedule(),
+        );
+        energy_mapper.set(new_energy.get_energy_entry());
+        self.notify_energy_subscribers(
+            user,
+            prev_energy.get_energy_entry(),
+            new_energy_depleted.get_energy_entry(),
+        );
+        self.emit_energy_updated_event(
+            user,
+            prev_energy.into_energy_entry(),
+            new_energy.into_energy_entry(),
+        );
+    }
+
+    /// Only writes the buckets that changed
+    fn save_user_unlock_schedule(
+        &self,
+        user: &ManagedAddress,
+        prev_schedule: &UnlockSchedule<Self::Api>,
+        new_schedule: &UnlockSchedule<Self::Api>,
+    ) {
+        let mut unlock_epochs_mapper = self.user_unlock_epochs(user);
+        for prev_bucket in prev_schedule.iter() {
+            if !new_schedule
+                .iter()
+                .any(|bucket| bucket.unlock_epoch == prev_bucket.unlock_epoch)
+            {
+                let _ = unlock_epochs_mapper.swap_remove(&prev_bucket.unlock_epoch);
+                self.user_unlock_bucket(user, prev_bucket.unlock_epoch)
+                    .clear();
+            }
+        }
+
+        for new_bucket in new_schedule.iter() {
+            if !prev_schedule.iter().any(|bucket| bucket == new_bucket) {
+                let _ = unlock_epochs_mapper.insert(new_bucket.unlock_epoch);
+                self.user_unlock_bucket(user, new_bucket.unlock_epoch)
+                    .set(&new_bucket);
+            }
+        }
+    }
+
+    /// Both entries are expected to be depleted up to the current epoch
+    fn update_total_energy(
+        &self,
+        prev_energy: &UserEnergy<Self::Api>,
+        new_energy: &UserEnergy<Self::Api>,
+    ) {
+        let mut total_energy = self.get_updated_total_energy_entry();
+        total_energy.remove_user_energy(prev_energy);
+        total_energy.add_user_energy(new_energy);
+        self.total_energy().set(&total_energy);
+
+        self.update_depletion_rate_ends(
+            prev_energy,
+            new_energy,
+            |epoch| self.total_energy_depletion_rate_end(epoch),
+            |epoch| self.total_energy_refund(epoch),
+        );
+    }
+
+    /// Both entries are expected to be depleted up to the current epoch
+    fn update_delegated_energy(
+        &self,
+        delegatee: &ManagedAddress,
+        prev_energy: &UserEnergy<Self::Api>,
+        new_energy: &UserEnergy<Self::Api>,
+    ) {
+        let mut delegated_energy = self.get_updated_delegated_energy_entry(delegatee);
+        delegated_energy.remove_user_energy(prev_energy);
+        delegated_energy.add_user_energy(new_energy);
+        self.delegated_energy(delegatee).set(&delegated_energy);
+
+        self.update_depletion_rate_ends(
+            prev_energy,
+            new_energy,
+            |epoch| self.delegated_energy_depletion_rate_end(delegatee, epoch),
+            |epoch| self.delegated_energy_refund(delegatee, epoch),
+        );
+    }
+
+    /// Moves the user's depletion rate ends (see `UserEnergy::get_depletion_rate_ends`)
+    /// to the aggregate's, keyed by epoch. Only the changed ones are written.
+    /// Both entries are expected to be depleted up to the current epoch.
+    fn update_depletion_rate_ends<
+        F: Fn(Epoch) -> SingleValueMapper<BigUint>,
+        G: Fn(Epoch) -> SingleValueMapper<BigUint>,
+    >(
+        &self,
+        prev_energy: &UserEnergy<Self::Api>,
+        new_energy: &UserEnergy<Self::Api>,
+        depletion_rate_end_mapper: F,
+        energy_refund_mapper: G,
+    ) {
+        let prev_rate_ends = prev_energy.get_depletion_rate_ends();
+        let new_rate_ends = new_energy.get_depletion_rate_ends();
+        for prev_rate_end in prev_rate_ends.iter() {
+            let (new_depletion_rate, new_energy_refund) = match new_rate_ends
+                .iter()
+                .find(|rate_end| rate_end.epoch == prev_rate_end.epoch)
+            {
+                Some(new_rate_end) => (new_rate_end.depletion_rate, new_rate_end.energy_refund),
+                None => (BigUint::zero(), BigUint::zero()),
+            };
+
+            update_aggregated_value(
+                depletion_rate_end_mapper(prev_rate_end.epoch),
+                &prev_rate_end.depletion_rate,
+                &new_depletion_rate,
+            );
+            update_aggregated_value(
+                energy_refund_mapper(prev_rate_end.epoch),
+                &prev_rate_end.energy_refund,
+                &new_energy_refund,
+            );
+        }
+
+        let zero = BigUint::zero();
+        for new_rate_end in new_rate_ends.iter() {
+            if prev_rate_ends
+                .iter()
+                .any(|rate_end| rate_end.epoch == new_rate_end.epoch)
+            {
+                continue;
+            }
+
+            update_aggregated_value(
+                depletion_rate_end_mapper(new_rate_end.epoch),
+                &zero,
+                &new_rate_end.depletion_rate,
+            );
+            update_aggregated_value(
+                energy_refund_mapper(new_rate_end.epoch),
+                &zero,
+                &new_rate_end.energy_refund,
+            );
+        }
     }
 
     #[view(getEnergyEntryForUser)]
     fn get_updated_energy_entry_for_user(&self, user: &ManagedAddress) -> Energy<Self::Api> {
-        let current_epoch = self.blockchain().get_block_epoch();
+        self.get_updated_user_energy(user).into_energy_entry()
+    }
+
+    fn get_updated_user_energy(&self, user: &ManagedAddress) -> UserEnergy<Self::Api> {
+        let energy_epoch = self.get_energy_epoch();
         let energy_mapper = self.user_energy(user);
         if !energy_mapper.is_empty() {
-            let mut energy = energy_mapper.get();
-            energy.deplete(current_epoch);
+            let mut energy =
+                UserEnergy::new(energy_mapper.get(), self.get_user_unlock_schedule(user));
+            energy.deplete(energy_epoch);
 
             energy
         } else {
-            Energy::new_zero_energy(current_epoch)
+            UserEnergy::new_zero_energy(energy_epoch)
         }
     }
 
+    /// Entries saved before checkpoints (and the total energy) existed have no checkpoints
+    fn is_legacy_energy_entry(&self, user: &ManagedAddress) -> bool {
+        self.user_energy_checkpoints(user).is_empty() && !self.user_energy(user).is_empty()
+    }
+
+    fn add_legacy_energy_checkpoint(&self, user: &ManagedAddress) {
+        let legacy_energy = UserEnergy::new(
+            self.user_energy(user).get(),
+            self.get_user_unlock_schedule(user),
+        );
+        self.add_energy_checkpoint(user, &legacy_energy);
+    }
+
+    fn get_user_unlock_schedule(&self, user: &ManagedAddress) -> UnlockSchedule<Self::Api> {
+        let mut unlock_schedule = ManagedVec::new();
+        for unlock_epoch in self.user_unlock_epochs(user).iter() {
+            unlock_schedule.push(self.user_unlock_bucket(user, unlock_epoch).get());
+        }
+
+        unlock_schedule
+    }
+
     #[view(getEnergyAmountForUser)]
     fn get_energy_amount_for_user(&self, user: ManagedAddress) -> BigUint {
-        let energy = self.get_updated_energy_entry_for_user(&user);
+        let energy = self.get_updated_user_energy(&user);
 
         energy.get_energy_amount()
     }
 
+    /// Returns the updated energy entries, in the same order as the given users.
+    /// Accepts at most `MAX_USERS_PER_ENERGY_QUERY` users.
+    #[view(getEnergyEntriesForUsers)]
+    fn get_energy_entries_for_users(
+        &self,
+        users: MultiValueEncoded<ManagedAddress>,
+    ) -> MultiValueEncoded<Energy<Self::Api>> {
+        require!(users.len() <= MAX_USERS_PER_ENERGY_QUERY, "Too many users");
+
+        let mut result = MultiValueEncoded::new();
+        for user in users {
+            let energy = self.get_updated_energy_entry_for_user(&user);
+            result.push(energy);
+        }
+
+        result
+    }
+
+    /// Returns the energy amounts, in the same order as the given users.
+    /// Accepts at most `MAX_USERS_PER_ENERGY_QUERY` users.
+    #[view(getEnergyAmountsForUsers)]
+    fn get_energy_amounts_for_users(
+        &self,
+        users: MultiValueEncoded<ManagedAddress>,
+    ) -> MultiValueEncoded<BigUint> {
+        require!(users.len() <= MAX_USERS_PER_ENERGY_QUERY, "Too many users");
+
+        let mut result = MultiValueEncoded::new();
+        for user in users {
+            let energy_amount = self.get_energy_amount_for_user(user);
+            result.push(energy_amount);
+        }
+
+        result
+    }
+
+    fn get_updated_total_energy_entry(&self) -> AggregatedEnergy<Self::Api> {
+        let energy_epoch = self.get_energy_epoch();
+        let total_energy_mapper = self.total_energy();
+        if !total_energy_mapper.is_empty() {
+            let mut total_energy = total_energy_mapper.get();
+            total_energy.deplete(energy_epoch, |epoch| {
+                (
+                    self.total_energy_depletion_rate_end(epoch).get(),
+                    self.total_energy_refund(epoch).get(),
+                )
+            });
+
+            total_energy
+        } else {
+            AggregatedEnergy::new_zero_energy(energy_epoch)
+        }
+    }
+
+    /// The sum of the energy entries delegated to the given address
+    fn get_updated_delegated_energy_entry(
+        &self,
+        delegatee: &ManagedAddress,
+    ) -> AggregatedEnergy<Self::Api> {
+        let energy_epoch = self.get_energy_epoch();
+        let delegated_energy_mapper = self.delegated_energy(delegatee);
+        if !delegated_energy_mapper.is_empty() {
+            let mut delegated_energy = delegated_energy_mapper.get();
+            delegated_energy.deplete(energy_epoch, |epoch| {
+                (
+                    self.delegated_energy_depletion_rate_end(delegatee, epoch)
+                        .get(),
+                    self.delegated_energy_refund(delegatee, epoch).get(),
+                )
+            });
+
+            delegated_energy
+        } else {
+            AggregatedEnergy::new_zero_energy(energy_epoch)
+        }
+    }
+
+    /// The sum of all the users' energy amounts
+    #[view(getTotalEnergy)]
+    fn get_total_energy(&self) -> BigUint {
+        let total_energy = self.get_updated_total_energy_entry();
+
+        total_energy.get_energy_amount()
+    }
+
+    #[view(getTotalLockedTokens)]
+    fn get_total_locked_tokens(&self) -> BigUint {
+        let total_energy = self.get_updated_total_energy_entry();
+
+        total_energy.get_total_locked_tokens().clone()
+    }
+
+    /// Returns the energy the user had at the given past (or current) epoch,
+    /// by depleting the last checkpoint recorded before it up to that epoch.
+    /// Users without checkpoints fall back to their stored entry, if last updated by then.
+    /// The epoch is adjusted according to the current decay mode.
+    #[view(getEnergyAtEpoch)]
+    fn get_energy_at_epoch(&self, user: ManagedAddress, epoch: Epoch) -> BigUint {
+        let current_epoch = self.blockchain().get_block_epoch();
+        require!(epoch <= current_epoch, "Epoch cannot be in the future");
+
+        let energy_epoch = self.get_energy_epoch_at(epoch);
+        let opt_energy = match self.find_energy_checkpoint(&user, energy_epoch) {
+            Some(energy) => Some(energy),
+            None => self.find_legacy_energy_entry(&user, energy_epoch),
+        };
+        match opt_energy {
+            Some(mut energy) => {
+                energy.deplete(energy_epoch);
+                energy.get_energy_amount()
+            }
+            None => BigUint::zero(),
+        }
+    }
+
+    /// Entries saved before checkpoints existed only become a checkpoint on their next update
+    fn find_legacy_energy_entry(
+        &self,
+        user: &ManagedAddress,
+        epoch: Epoch,
+    ) -> Option<UserEnergy<Self::Api>> {
+        if !self.user_energy_checkpoints(user).is_empty() {
+            return None;
+        }
+
+        let energy_mapper = self.user_energy(user);
+        if energy_mapper.is_empty() {
+            return None;
+        }
+
+        let energy = energy_mapper.get();
+        if energy.get_last_update_epoch() <= epoch {
+            Some(UserEnergy::new(energy, self.get_user_unlock_schedule(user)))
+        } else {
+            None
+        }
+    }
+
     #[storage_mapper("userEnergy")]
     fn user_energy(&self, user: &ManagedAddress) -> SingleValueMapper<Energy<Self::Api>>;
+
+    #[storage_mapper("userUnlockEpochs")]
+    fn user_unlock_epochs(&self, user: &ManagedAddress) -> UnorderedSetMapper<Epoch>;
+
+    #[storage_mapper("userUnlockBucket")]
+    fn user_unlock_bucket(
+        &self,
+        user: &ManagedAddress,
+        unlock_epoch: Epoch,
+    ) -> SingleValueMapper<UnlockBucket<Self::Api>>;
+
+    #[storage_mapper("energyDelegatee")]
+    fn energy_delegatee(&self, user: &ManagedAddress) -> SingleValueMapper<ManagedAddress>;
+
+    #[storage_mapper("delegatedEnergy")]
+    fn delegated_energy(
+        &self,
+        delegatee: &ManagedAddress,
+    ) -> SingleValueMapper<AggregatedEnergy<Self::Api>>;
+
+    #[storage_mapper("delegatedEnergyDepletionRateEnd")]
+    fn delegated_energy_depletion_rate_end(
+        &self,
+        delegatee: &ManagedAddress,
+        epoch: Epoch,
+    ) -> SingleValueMapper<BigUint>;
+
+    #[storage_mapper("delegatedEnergyRefund")]
+    fn delegated_energy_refund(
+        &self,
+        delegatee: &ManagedAddress,
+        epoch: Epoch,
+    ) -> SingleValueMapper<BigUint>;
+
+    #[storage_mapper("totalEnergy")]
+    fn total_energy(&self) -> SingleValueMapper<AggregatedEnergy<Self::Api>>;
+
+    #[storage_mapper("totalEnergyDepletionRateEnd")]
+    fn total_energy_depletion_rate_end(&self, epoch: Epoch) -> SingleValueMapper<BigUint>;
+
+    #[storage_mapper("totalEnergyRefund")]
+    fn total_energy_refund(&self, epoch: Epoch) -> SingleValueMapper<BigUint>;
+}
+
+fn saturating_sub<M: ManagedTypeApi>(first: &BigUint<M>, second: &BigUint<M>) -> BigUint<M> {
+    if first > second {
+        first - second
+    } else {
+        BigUint::zero()
+    }
+}
+
+/// Saturates at zero, see `AggregatedEnergy`
+fn update_aggregated_value<M: multiversx_sc::api::StorageMapperApi>(
+    mapper: SingleValueMapper<M, BigUint<M>>,
+    prev_value: &BigUint<M>,
+    new_value: &BigUint<M>,
+) {
+    if new_value < prev_value {
+        let removed_value = prev_value - new_value;
+        mapper.update(|value| *value = saturating_sub(value, &removed_value));
+    } else if new_value > prev_value {
+        let added_value = new_value - prev_value;
+        mapper.update(|value| *value += added_value);
+    }
 }
@@ -0,0 +1,51 @@
+multiversx_sc::imports!();
+
+use common_structs::Epoch;
+
+use crate::energy::UserEnergy;
+
+#[multiversx_sc::module]
+pub trait EnergyCheckpointModule: crate::energy_decay::EnergyDecayModule {
+    /// Expects the energy entry to be depleted up to the current epoch.
+    /// Only one checkpoint is kept per epoch, the last one overwriting the previous.
+    fn add_energy_checkpoint(&self, user: &ManagedAddress, energy: &UserEnergy<Self::Api>) {
+        let mut checkpoints_mapper = self.user_energy_checkpoints(user);
+        let last_index = checkpoints_mapper.len();
+        if last_index > 0 {
+            let last_checkpoint = checkpoints_mapper.get(last_index);
+            if last_checkpoint.get_last_update_epoch() == energy.get_last_update_epoch() {
+                checkpoints_mapper.set(last_index, energy);
+                return;
+            }
+        }
+
+        let _ = checkpoints_mapper.push(energy);
+    }
+
+    /// Returns the last checkpoint recorded at or before the given epoch, if any
+    fn find_energy_checkpoint(
+        &self,
+        user: &ManagedAddress,
+        epoch: Epoch,
+    ) -> Option<UserEnergy<Self::Api>> {
+        let checkpoints_mapper = self.user_energy_checkpoints(user);
+        let mut low = 1;
+        let mut high = checkpoints_mapper.len();
+        let mut result = None;
+        while low <= high {
+            let mid = low + (high - low) / 2;
+            let checkpoint = checkpoints_mapper.get(mid);
+            if checkpoint.get_last_update_epoch() <= epoch {
+                result = Some(checkpoint);
+                low = mid + 1;
+            } else {
+                high = mid - 1;
+            }
+        }
+
+        result
+    }
+
+    #[storage_mapper("userEnergyCheckpoints")]
+    fn user_energy_checkpoints(&self, user: &ManagedAddress) -> VecMapper<UserEnergy<Self::Api>>;
+}
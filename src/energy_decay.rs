@@ -0,0 +1,69 @@
+multiversx_sc::imports!();
+multiversx_sc::derive_imports!();
+
+use common_structs::Epoch;
+
+pub const EPOCHS_PER_WEEK: Epoch = 7;
+
+#[derive(
+    TypeAbi, TopEncode, TopDecode, NestedEncode, NestedDecode, Clone, Copy, PartialEq, Debug,
+)]
+pub enum EnergyDecayMode {
+    /// Energy decays every epoch
+    Continuous,
+    /// Energy decays in steps, at the start of every week
+    Weekly,
+}
+
+#[multiversx_sc::module]
+pub trait EnergyDecayModule {
+    /// Sets how energy decays:
+    /// - Continuous: every epoch (default)
+    /// - Weekly: every 7 epochs, at epochs divisible by 7.
+    ///     Energy is then computed as of the start of the current week.
+    #[only_owner]
+    #[endpoint(setEnergyDecayMode)]
+    fn set_energy_decay_mode(&self, decay_mode: EnergyDecayMode) {
+        let decay_mode_mapper = self.energy_decay_mode();
+        require!(
+            decay_mode_mapper.get() != decay_mode,
+            "Decay mode already set"
+        );
+
+        let current_epoch = self.blockchain().get_block_epoch();
+        decay_mode_mapper.set(decay_mode);
+        self.energy_decay_mode_start_epoch().set(current_epoch);
+    }
+
+    /// The epoch all energy entries are depleted up to
+    fn get_energy_epoch(&self) -> Epoch {
+        let current_epoch = self.blockchain().get_block_epoch();
+
+        self.get_energy_epoch_at(current_epoch)
+    }
+
+    /// With weekly decay, energy is computed as of the start of the given epoch's week.
+    /// The first week only starts at the epoch the mode was set,
+    /// so entries are never depleted up to an epoch before their last update.
+    fn get_energy_epoch_at(&self, epoch: Epoch) -> Epoch {
+        match self.energy_decay_mode().get() {
+            EnergyDecayMode::Continuous => epoch,
+            EnergyDecayMode::Weekly => {
+                let mode_start_epoch = self.energy_decay_mode_start_epoch().get();
+                if epoch < mode_start_epoch {
+                    return epoch;
+                }
+
+                let week_start_epoch = epoch - epoch % EPOCHS_PER_WEEK;
+                core::cmp::max(week_start_epoch, mode_start_epoch)
+            }
+        }
+    }
+
+    #[view(getEnergyDecayMode)]
+    #[storage_mapper("energyDecayMode")]
+    fn energy_decay_mode(&self) -> SingleValueMapper<EnergyDecayMode>;
+
+    #[storage_mapper("energyDecayModeStartEpoch")]
+    fn energy_decay_mode_start_epoch(&self) -> SingleValueMapper<Epoch>;
+}
@@ -0,0 +1,103 @@
+multiversx_sc::imports!();
+
+use crate::energy::UserEnergy;
+
+#[multiversx_sc::module]
+pub trait EnergyDelegationModule:
+    crate::energy::EnergyModule
+    + crate::energy_checkpoint::EnergyCheckpointModule
+    + crate::energy_decay::EnergyDecayModule
+    + crate::energy_subscribers::EnergySubscribersModule
+    + crate::events::EventsModule
+    + multiversx_sc_modules::pause::PauseModule
+{
+    /// Delegates the caller's whole energy to the given address, replacing any previous delegatee.
+    /// The delegated energy follows any further updates of the caller's energy entry.
+    /// Energy delegated to an address is not delegated further by it.
+    #[endpoint(delegateEnergy)]
+    fn delegate_energy(&self, delegatee: ManagedAddress) {
+        self.require_not_paused();
+
+        let caller = self.blockchain().get_caller();
+        require!(!delegatee.is_zero(), "Invalid delegatee");
+        require!(caller != delegatee, "Cannot delegate energy to self");
+
+        let delegatee_mapper = self.energy_delegatee(&caller);
+        self.update_energy(&caller, |energy: &mut UserEnergy<Self::Api>| {
+            let zero_energy = UserEnergy::new_zero_energy(energy.get_last_update_epoch());
+            if !delegatee_mapper.is_empty() {
+                let prev_delegatee = delegatee_mapper.get();
+                require!(
+                    prev_delegatee != delegatee,
+                    "Energy already delegated to this address"
+                );
+
+                self.update_delegated_energy(&prev_delegatee, energy, &zero_energy);
+                self.emit_energy_undelegated_event(
+                    &caller,
+                    &prev_delegatee,
+                    energy.get_energy_entry().clone(),
+                );
+            }
+
+            self.update_delegated_energy(&delegatee, &zero_energy, energy);
+            delegatee_mapper.set(&delegatee);
+            self.emit_energy_delegated_event(
+                &caller,
+                &delegatee,
+                energy.get_energy_entry().clone(),
+            );
+        });
+    }
+
+    #[endpoint(undelegateEnergy)]
+    fn undelegate_energy(&self) {
+        self.require_not_paused();
+
+        let caller = self.blockchain().get_caller();
+        let delegatee_mapper = self.energy_delegatee(&caller);
+        require!(!delegatee_mapper.is_empty(), "Energy not delegated");
+
+        let delegatee = delegatee_mapper.take();
+        self.update_energy(&caller, |energy: &mut UserEnergy<Self::Api>| {
+            let zero_energy = UserEnergy::new_zero_energy(energy.get_last_update_epoch());
+            self.update_delegated_energy(&delegatee, energy, &zero_energy);
+            self.emit_energy_undelegated_event(
+                &caller,
+                &delegatee,
+                energy.get_energy_entry().clone(),
+            );
+        });
+    }
+
+    #[view(getEnergyDelegatee)]
+    fn get_energy_delegatee(&self, user: ManagedAddress) -> OptionalValue<ManagedAddress> {
+        let delegatee_mapper = self.energy_delegatee(&user);
+        if !delegatee_mapper.is_empty() {
+            OptionalValue::Some(delegatee_mapper.get())
+        } else {
+            OptionalValue::None
+        }
+    }
+
+    /// The energy other users delegated to the given address
+    #[view(getDelegatedEnergyForUser)]
+    fn get_delegated_energy_for_user(&self, user: ManagedAddress) -> BigUint {
+        let delegated_energy = self.get_updated_delegated_energy_entry(&user);
+
+        delegated_energy.get_energy_amount()
+    }
+
+    /// The user's own energy, plus the energy delegated to them,
+    /// minus their own energy if they delegated it to another address
+    #[view(getEffectiveEnergyForUser)]
+    fn get_effective_energy_for_user(&self, user: ManagedAddress) -> BigUint {
+        let delegated_in_energy = self.get_updated_delegated_energy_entry(&user);
+        let mut effective_energy = delegated_in_energy.get_energy_amount();
+        if self.energy_delegatee(&user).is_empty() {
+            effective_energy += self.get_energy_amount_for_user(user);
+        }
+
+        effective_energy
+    }
+}
@@ -0,0 +1,65 @@
+multiversx_sc::imports!();
+
+use simple_lock::locked_token::LockedTokenAttributes;
+
+use crate::energy::UserEnergy;
+
+#[multiversx_sc::module]
+pub trait EnergyReconciliationModule:
+    simple_lock::locked_token::LockedTokenModule
+    + simple_lock::token_attributes::TokenAttributesModule
+    + multiversx_sc_modules::default_issue_callbacks::DefaultIssueCallbacksModule
+    + multiversx_sc_modules::pause::PauseModule
+    + crate::energy::EnergyModule
+    + crate::energy_checkpoint::EnergyCheckpointModule
+    + crate::energy_decay::EnergyDecayModule
+    + crate::energy_subscribers::EnergySubscribersModule
+    + crate::events::EventsModule
+    + utils::UtilsModule
+{
+    /// Rebuilds the caller's energy from the LOCKED tokens they send,
+    /// based on each token's unlock epoch. The tokens are sent back to the caller.
+    ///
+    /// The caller is expected to send all their LOCKED tokens,
+    /// as the energy of any tokens not sent (including legacy tokens) is discarded.
+    /// Tokens keep the energy multiplier weight of their unlock epoch's bucket, if any,
+    /// up to the bucket's amount. Any other tokens are weighted 1x.
+    ///
+    /// Expected payments: LOCKED tokens
+    #[payable("*")]
+    #[endpoint(recomputeEnergy)]
+    fn recompute_energy(&self) {
+        self.require_not_paused();
+
+        let caller = self.blockchain().get_caller();
+        let payments = self.get_non_empty_payments();
+        let locked_token_mapper = self.locked_token();
+        locked_token_mapper.require_all_same_token(&payments);
+
+        let energy_epoch = self.get_energy_epoch();
+        let prev_energy = self.get_updated_user_energy(&caller);
+        let mut remaining_energy = prev_energy.clone();
+        let mut new_energy = UserEnergy::new_zero_energy(energy_epoch);
+        for payment in &payments {
+            let attributes: LockedTokenAttributes<Self::Api> =
+                locked_token_mapper.get_token_attributes(payment.token_nonce);
+            let weighted_amount =
+                remaining_energy.take_weighted_amount(attributes.unlock_epoch, &payment.amount);
+            new_energy.add_after_weighted_token_lock(
+                &payment.amount,
+                &weighted_amount,
+                attributes.unlock_epoch,
+                energy_epoch,
+            );
+        }
+
+        self.set_user_energy(&caller, new_energy.clone());
+        self.emit_energy_recomputed_event(
+            &caller,
+            prev_energy.into_energy_entry(),
+            new_energy.into_energy_entry(),
+        );
+
+        self.send().direct_multi(&caller, &payments);
+    }
+}
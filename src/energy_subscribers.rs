@@ -0,0 +1,142 @@
+multiversx_sc::imports!();
+multiversx_sc::derive_imports!();
+
+use crate::energy::Energy;
+
+pub const MAX_ENERGY_SUBSCRIBERS: usize = 10;
+
+pub mod energy_subscriber_proxy {
+    multiversx_sc::imports!();
+
+    use crate::energy::Energy;
+
+    #[multiversx_sc::proxy]
+    pub trait EnergySubscriberProxy {
+        #[endpoint(onEnergyUpdated)]
+        fn on_energy_updated(
+            &self,
+            user: ManagedAddress,
+            old_energy: Energy<Self::Api>,
+            new_energy: Energy<Self::Api>,
+        );
+    }
+}
+
+#[derive(TypeAbi, TopEncode, TopDecode, NestedEncode, NestedDecode, Clone, PartialEq, Debug)]
+pub struct EnergySubscriberConfig {
+    pub enabled: bool,
+    pub gas_limit: u64,
+}
+
+#[multiversx_sc::module]
+pub trait EnergySubscribersModule {
+    /// Registers a SC to be notified through `onEnergyUpdated(user, old_energy, new_energy)`
+    /// every time a user's energy entry changes.
+    /// The call is synchronous, so a failing subscriber reverts the whole transaction.
+    /// Subscribers are enabled by default.
+    #[only_owner]
+    #[endpoint(addEnergySubscriber)]
+    fn add_energy_subscriber(&self, sc_address: ManagedAddress, gas_limit: u64) {
+        require!(
+            self.blockchain().is_smart_contract(&sc_address),
+            "Invalid SC address"
+        );
+        require!(gas_limit > 0, "Invalid gas limit");
+        require!(
+            self.energy_subscribers().len() < MAX_ENERGY_SUBSCRIBERS,
+            "Too many energy subscribers"
+        );
+
+        let is_new = self.energy_subscribers().insert(sc_address.clone());
+        require!(is_new, "Energy subscriber already added");
+
+        self.energy_subscriber_config(&sc_address)
+            .set(EnergySubscriberConfig {
+                enabled: true,
+                gas_limit,
+            });
+    }
+
+    #[only_owner]
+    #[endpoint(removeEnergySubscriber)]
+    fn remove_energy_subscriber(&self, sc_address: ManagedAddress) {
+        let was_removed = self.energy_subscribers().swap_remove(&sc_address);
+        require!(was_removed, "Unknown energy subscriber");
+
+        self.energy_subscriber_config(&sc_address).clear();
+    }
+
+    #[only_owner]
+    #[endpoint(setEnergySubscriberEnabled)]
+    fn set_energy_subscriber_enabled(&self, sc_address: ManagedAddress, enabled: bool) {
+        self.require_energy_subscriber(&sc_address);
+        self.energy_subscriber_config(&sc_address)
+            .update(|config| config.enabled = enabled);
+    }
+
+    #[only_owner]
+    #[endpoint(setEnergySubscriberGasLimit)]
+    fn set_energy_subscriber_gas_limit(&self, sc_address: ManagedAddress, gas_limit: u64) {
+        self.require_energy_subscriber(&sc_address);
+        require!(gas_limit > 0, "Invalid gas limit");
+
+        self.energy_subscriber_config(&sc_address)
+            .update(|config| config.gas_limit = gas_limit);
+    }
+
+    /// Returns pairs of (subscriber address, config)
+    #[view(getEnergySubscribers)]
+    fn get_energy_subscribers(
+        &self,
+    ) -> MultiValueEncoded<MultiValue2<ManagedAddress, EnergySubscriberConfig>> {
+        let mut result = MultiValueEncoded::new();
+        for sc_address in self.energy_subscribers().iter() {
+            let config = self.energy_subscriber_config(&sc_address).get();
+            result.push((sc_address, config).into());
+        }
+
+        result
+    }
+
+    fn notify_energy_subscribers(
+        &self,
+        user: &ManagedAddress,
+        old_energy: &Energy<Self::Api>,
+        new_energy: &Energy<Self::Api>,
+    ) {
+        for sc_address in self.energy_subscribers().iter() {
+            let config = self.energy_subscriber_config(&sc_address).get();
+            if !config.enabled {
+                continue;
+            }
+
+            let _: IgnoreValue = self
+                .energy_subscriber_proxy_obj(sc_address)
+                .on_energy_updated(user.clone(), old_energy.clone(), new_energy.clone())
+                .with_gas_limit(config.gas_limit)
+                .execute_on_dest_context();
+        }
+    }
+
+    fn require_energy_subscriber(&self, sc_address: &ManagedAddress) {
+        require!(
+            self.energy_subscribers().contains(sc_address),
+            "Unknown energy subscriber"
+        );
+    }
+
+    #[proxy]
+    fn energy_subscriber_proxy_obj(
+        &self,
+        sc_address: ManagedAddress,
+    ) -> energy_subscriber_proxy::Proxy<Self::Api>;
+
+    #[storage_mapper("energySubscribers")]
+    fn energy_subscribers(&self) -> UnorderedSetMapper<ManagedAddress>;
+
+    #[storage_mapper("energySubscriberConfig")]
+    fn energy_subscriber_config(
+        &self,
+        sc_address: &ManagedAddress,
+    ) -> SingleValueMapper<EnergySubscriberConfig>;
+}
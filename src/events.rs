@@ -1,7 +1,9 @@
 multiversx_sc::imports!();
 multiversx_sc::derive_imports!();
 
-use crate::energy::Energy;
+use common_structs::Epoch;
+
+use crate::{energy::Energy, penalty_distribution::PenaltyDestination};
 
 #[derive(TypeAbi, TopEncode, TopDecode)]
 pub struct EnergyUpdatedEvent<M: ManagedTypeApi> {
@@ -9,6 +11,13 @@ pub struct EnergyUpdatedEvent<M: ManagedTypeApi> {
     pub new_energy_entry: Energy<M>,
 }
 
+#[derive(TypeAbi, TopEncode, TopDecode)]
+pub struct PenaltyCollectedEvent<M: ManagedTypeApi> {
+    pub amount: BigUint<M>,
+    pub prev_lock_epochs: Epoch,
+    pub new_lock_epochs: Epoch,
+}
+
 #[multiversx_sc::module]
 pub trait EventsModule {
     fn emit_energy_updated_event(
@@ -30,6 +39,94 @@ pub trait EventsModule {
         );
     }
 
+    fn emit_energy_recomputed_event(
+        &self,
+        user: &ManagedAddress,
+        old_energy_entry: Energy<Self::Api>,
+        new_energy_entry: Energy<Self::Api>,
+    ) {
+        let data = EnergyUpdatedEvent {
+            old_energy_entry,
+            new_energy_entry,
+        };
+        self.energy_recomputed_event(
+            user,
+            self.blockchain().get_block_nonce(),
+            self.blockchain().get_block_epoch(),
+            self.blockchain().get_block_timestamp(),
+            data,
+        );
+    }
+
+    fn emit_energy_delegated_event(
+        &self,
+        delegator: &ManagedAddress,
+        delegatee: &ManagedAddress,
+        energy_entry: Energy<Self::Api>,
+    ) {
+        self.energy_delegated_event(
+            delegator,
+            delegatee,
+            self.blockchain().get_block_nonce(),
+            self.blockchain().get_block_epoch(),
+            self.blockchain().get_block_timestamp(),
+            energy_entry,
+        );
+    }
+
+    fn emit_energy_undelegated_event(
+        &self,
+        delegator: &ManagedAddress,
+        delegatee: &ManagedAddress,
+        energy_entry: Energy<Self::Api>,
+    ) {
+        self.energy_undelegated_event(
+            delegator,
+            delegatee,
+            self.blockchain().get_block_nonce(),
+            self.blockchain().get_block_epoch(),
+            self.blockchain().get_block_timestamp(),
+            energy_entry,
+        );
+    }
+
+    fn emit_penalty_distributed_event(
+        &self,
+        user: &ManagedAddress,
+        destination: PenaltyDestination,
+        amount: BigUint,
+    ) {
+        self.penalty_distributed_event(
+            user,
+            destination,
+            self.blockchain().get_block_nonce(),
+            self.blockchain().get_block_epoch(),
+            self.blockchain().get_block_timestamp(),
+            amount,
+        );
+    }
+
+    fn emit_penalty_collected_event(
+        &self,
+        user: &ManagedAddress,
+        amount: BigUint,
+        prev_lock_epochs: Epoch,
+        new_lock_epochs: Epoch,
+    ) {
+        let data = PenaltyCollectedEvent {
+            amount,
+            prev_lock_epochs,
+            new_lock_epochs,
+        };
+        self.penalty_collected_event(
+            user,
+            self.blockchain().get_block_nonce(),
+            self.blockchain().get_block_epoch(),
+            self.blockchain().get_block_timestamp(),
+            data,
+        );
+    }
+
     #[event("energyUpdated")]
     fn energy_updated_event(
         &self,
@@ -39,4 +136,57 @@ pub trait EventsModule {
         #[indexed] timestamp: u64,
         data: EnergyUpdatedEvent<Self::Api>,
     );
+
+    #[event("energyRecomputed")]
+    fn energy_recomputed_event(
+        &self,
+        #[indexed] caller: &ManagedAddress,
+        #[indexed] block: u64,
+        #[indexed] epoch: u64,
+        #[indexed] timestamp: u64,
+        data: EnergyUpdatedEvent<Self::Api>,
+    );
+
+    #[event("energyDelegated")]
+    fn energy_delegated_event(
+        &self,
+        #[indexed] delegator: &ManagedAddress,
+        #[indexed] delegatee: &ManagedAddress,
+        #[indexed] block: u64,
+        #[indexed] epoch: u64,
+        #[indexed] timestamp: u64,
+        energy_entry: Energy<Self::Api>,
+    );
+
+    #[event("energyUndelegated")]
+    fn energy_undelegated_event(
+        &self,
+        #[indexed] delegator: &ManagedAddress,
+        #[indexed] delegatee: &ManagedAddress,
+        #[indexed] block: u64,
+        #[indexed] epoch: u64,
+        #[indexed] timestamp: u64,
+        energy_entry: Energy<Self::Api>,
+    );
+
+    #[event("penaltyDistributed")]
+    fn penalty_distributed_event(
+        &self,
+        #[indexed] user: &ManagedAddress,
+        #[indexed] destination: PenaltyDestination,
+        #[indexed] block: u64,
+        #[indexed] epoch: u64,
+        #[indexed] timestamp: u64,
+        amount: BigUint,
+    );
+
+    #[event("penaltyCollected")]
+    fn penalty_collected_event(
+        &self,
+        #[indexed] user: &ManagedAddress,
+        #[indexed] block: u64,
+        #[indexed] epoch: u64,
+        #[indexed] timestamp: u64,
+        data: PenaltyCollectedEvent<Self::Api>,
+    );
 }
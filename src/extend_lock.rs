@@ -3,7 +3,7 @@ multiversx_sc::imports!();
 use common_structs::Epoch;
 use simple_lock::locked_token::LockedTokenAttributes;
 
-use crate::energy::Energy;
+use crate::energy::UserEnergy;
 
 static INVALID_EXTEND_PERIOD_ARG_ERR_MSG: &[u8] =
     b"New lock period must be longer than the current one";
@@ -16,8 +16,12 @@ pub trait ExtendLockModule:
     + multiversx_sc_modules::default_issue_callbacks::DefaultIssueCallbacksModule
     + crate::token_whitelist::TokenWhitelistModule
     + crate::energy::EnergyModule
+    + crate::energy_checkpoint::EnergyCheckpointModule
+    + crate::energy_decay::EnergyDecayModule
+    + crate::energy_subscribers::EnergySubscribersModule
     + crate::lock_options::LockOptionsModule
     + crate::events::EventsModule
+    + crate::lock_cancellation::LockCancellationModule
     + crate::migration::SimpleLockMigrationModule
     + multiversx_sc_modules::pause::PauseModule
     + utils::UtilsModule
@@ -27,19 +31,25 @@ pub trait ExtendLockModule:
         &self,
         dest_address: &ManagedAddress,
         payment: EsdtTokenPayment,
+        lock_epochs: Epoch,
         unlock_epoch: Epoch,
-        current_epoch: Epoch,
     ) -> EsdtTokenPayment {
-        let output_payment = self.update_energy(dest_address, |energy: &mut Energy<Self::Api>| {
+        let output_payment = self.update_energy(dest_address, |energy| {
             let payment_clone = payment.clone();
             if self.is_base_asset_token(&payment.token_identifier) {
-                self.lock_base_asset(payment_clone, unlock_epoch, current_epoch, energy)
+                let output_tokens =
+                    self.lock_base_asset(payment_clone, lock_epochs, unlock_epoch, energy);
+                if dest_address == &self.blockchain().get_caller() {
+                    self.add_cancellable_lock(dest_address, &output_tokens);
+                }
+
+                output_tokens
             } else {
                 self.require_address_is_caller(dest_address);
                 self.locked_token()
                     .require_same_token(&payment.token_identifier);
 
-                self.extend_new_token_period(payment_clone, unlock_epoch, current_epoch, energy)
+                self.extend_new_token_period(payment_clone, lock_epochs, unlock_epoch, energy)
             }
         });
 
@@ -55,22 +65,30 @@ pub trait ExtendLockModule:
     fn lock_base_asset(
         &self,
         payment: EsdtTokenPayment,
+        lock_epochs: Epoch,
         unlock_epoch: Epoch,
-        current_epoch: Epoch,
-        energy: &mut Energy<Self::Api>,
+        energy: &mut UserEnergy<Self::Api>,
     ) -> EsdtTokenPayment {
+        let energy_epoch = self.get_energy_epoch();
         let output_tokens = self.lock_tokens(payment.into(), unlock_epoch);
-        energy.add_after_token_lock(&output_tokens.amount, unlock_epoch, current_epoch);
+        let weighted_amount = self.get_weighted_lock_amount(&output_tokens.amount, lock_epochs);
+        energy.add_after_weighted_token_lock(
+            &output_tokens.amount,
+            &weighted_amount,
+            unlock_epoch,
+            energy_epoch,
+        );
 
         self.to_esdt_payment(output_tokens)
     }
 
+    /// The extended tokens get the energy multiplier of the newly chosen lock option
     fn extend_new_token_period(
         &self,
         payment: EsdtTokenPayment,
+        lock_epochs: Epoch,
         new_unlock_epoch: Epoch,
-        current_epoch: Epoch,
-        energy: &mut Energy<Self::Api>,
+        energy: &mut UserEnergy<Self::Api>,
     ) -> EsdtTokenPayment {
         let attributes: LockedTokenAttributes<Self::Api> = self
             .locked_token()
@@ -81,11 +99,14 @@ pub trait ExtendLockModule:
             INVALID_EXTEND_PERIOD_ARG_ERR_MSG
         );
 
-        energy.update_after_unlock_epoch_change(
+        let energy_epoch = self.get_energy_epoch();
+        let weighted_amount = self.get_weighted_lock_amount(&payment.amount, lock_epochs);
+        energy.update_after_unlock_any(&payment.amount, attributes.unlock_epoch, energy_epoch);
+        energy.add_after_weighted_token_lock(
             &payment.amount,
-            attributes.unlock_epoch,
+            &weighted_amount,
             new_unlock_epoch,
-            current_epoch,
+            energy_epoch,
         );
 
         let unlocked_tokens = self.unlock_tokens_unchecked(payment, &attributes);
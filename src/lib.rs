@@ -3,16 +3,26 @@
 multiversx_sc::imports!();
 
 pub mod energy;
+pub mod energy_checkpoint;
+pub mod energy_decay;
+pub mod energy_delegation;
+pub mod energy_reconciliation;
+pub mod energy_subscribers;
 pub mod events;
 pub mod extend_lock;
 pub mod local_roles;
+pub mod lock_cancellation;
 pub mod lock_options;
 pub mod lock_options_endpoints;
 pub mod locked_token_transfer;
 pub mod migration;
 pub mod penalty;
+pub mod penalty_accounting;
+pub mod penalty_discount;
+pub mod penalty_distribution;
 pub mod token_merging;
 pub mod token_whitelist;
+pub mod unbond;
 pub mod unlock_with_penalty;
 pub mod unstake;
 pub mod virtual_lock;
@@ -20,9 +30,8 @@ pub mod virtual_lock;
 use common_structs::{Epoch, Percent};
 use mergeable::Mergeable;
 use simple_lock::locked_token::LockedTokenAttributes;
-use unwrappable::Unwrappable;
 
-use crate::energy::Energy;
+use crate::energy::{AggregatedEnergy, UserEnergy};
 
 #[multiversx_sc::contract]
 pub trait SimpleLockEnergy:
@@ -32,10 +41,17 @@ pub trait SimpleLockEnergy:
     + simple_lock::token_attributes::TokenAttributesModule
     + token_whitelist::TokenWhitelistModule
     + energy::EnergyModule
+    + energy_checkpoint::EnergyCheckpointModule
+    + energy_decay::EnergyDecayModule
+    + energy_delegation::EnergyDelegationModule
+    + energy_reconciliation::EnergyReconciliationModule
+    + energy_subscribers::EnergySubscribersModule
+    + lock_cancellation::LockCancellationModule
     + lock_options::LockOptionsModule
     + lock_options_endpoints::LockOptionsEndpointsModule
     + unlock_with_penalty::UnlockWithPenaltyModule
     + unstake::UnstakeModule
+    + unbond::UnbondModule
     + extend_lock::ExtendLockModule
     + migration::SimpleLockMigrationModule
     + events::EventsModule
@@ -43,6 +59,9 @@ pub trait SimpleLockEnergy:
     + local_roles::LocalRolesModule
     + token_merging::TokenMergingModule
     + penalty::LocalPenaltyModule
+    + penalty_discount::PenaltyDiscountModule
+    + penalty_accounting::PenaltyAccountingModule
+    + penalty_distribution::PenaltyDistributionModule
     + utils::UtilsModule
     + virtual_lock::VirtualLockModule
     + sc_whitelist_module::SCWhitelistModule
@@ -73,8 +92,7 @@ pub trait SimpleLockEnergy:
 
         self.add_lock_options(lock_options);
 
-        let all_lock_options = self.get_lock_options();
-        let max_lock_option = all_lock_options.last().unwrap_or_panic::<Self::Api>();
+        let max_lock_option = self.get_last_lock_option();
         require!(
             min_migrated_token_locked_period <= max_lock_option.lock_epochs,
             "Invalid min epoch for migrated token"
@@ -88,11 +106,24 @@ pub trait SimpleLockEnergy:
         self.old_locked_asset_factory_address()
             .set_if_empty(&old_locked_asset_factory_address);
 
+        self.total_energy()
+            .set(AggregatedEnergy::new_zero_energy(self.get_energy_epoch()));
+
         self.set_paused(true);
     }
 
+    /// The first upgrade since the total energy exists pauses the contract,
+    /// until the owner adds the users with entries saved beforehand, see `addUsersToTotalEnergy`.
     #[upgrade]
-    fn upgrade(&self) {}
+    fn upgrade(&self) {
+        self.migrate_legacy_lock_options();
+
+        let total_energy_mapper = self.total_energy();
+        if total_energy_mapper.is_empty() {
+            total_energy_mapper.set(AggregatedEnergy::new_zero_energy(self.get_energy_epoch()));
+            self.set_paused(true);
+        }
+    }
 
     /// Locks a whitelisted token until `unlock_epoch` and receive meta ESDT LOCKED tokens
     /// on a 1:1 ratio. Accepted input tokens:
@@ -120,14 +151,14 @@ pub trait SimpleLockEnergy:
         let payment = self.call_value().single_esdt();
         let dest_address = self.dest_from_optional(opt_destination);
         let current_epoch = self.blockchain().get_block_epoch();
-        let unlock_epoch = self.unlock_epoch_to_start_of_month(current_epoch + lock_epochs);
+        let unlock_epoch = self.round_unlock_epoch(current_epoch + lock_epochs);
         require!(
             unlock_epoch > current_epoch,
             "Unlock epoch must be greater than the current epoch"
         );
 
         let output_tokens =
-            self.lock_by_token_type(&dest_address, payment, unlock_epoch, current_epoch);
+            self.lock_by_token_type(&dest_address, payment, lock_epochs, unlock_epoch);
 
         self.send().direct_esdt(
             &dest_address,
@@ -149,14 +180,14 @@ pub trait SimpleLockEnergy:
     fn unlock_tokens_endpoint(&self) -> EsdtTokenPayment {
         self.require_not_paused();
 
-        let current_epoch = self.blockchain().get_block_epoch();
+        let energy_epoch = self.get_energy_epoch();
         let caller = self.blockchain().get_caller();
         let locked_token_mapper = self.locked_token();
 
         let base_asset = self.base_asset_token_id().get();
         let mut output_payment = EsdtTokenPayment::new(base_asset, 0, BigUint::zero());
 
-        self.update_energy(&caller, |energy: &mut Energy<Self::Api>| {
+        self.update_energy(&caller, |energy: &mut UserEnergy<Self::Api>| {
             let payments = self.get_non_empty_payments();
             locked_token_mapper.require_all_same_token(&payments);
 
@@ -165,10 +196,10 @@ pub trait SimpleLockEnergy:
                     locked_token_mapper.get_token_attributes(payment.token_nonce);
 
                 let unlocked_tokens = self.unlock_tokens(payment);
-                energy.refund_after_token_unlock(
+                energy.update_after_unlock_any(
                     &unlocked_tokens.amount,
                     attributes.unlock_epoch,
-                    current_epoch,
+                    energy_epoch,
                 );
 
                 output_payment.merge_with(self.to_esdt_payment(unlocked_tokens));
@@ -205,14 +236,14 @@ pub trait SimpleLockEnergy:
             .require_same_token(&payment.token_identifier);
 
         let current_epoch = self.blockchain().get_block_epoch();
-        let unlock_epoch = self.unlock_epoch_to_start_of_month(current_epoch + lock_epochs);
+        let unlock_epoch = self.round_unlock_epoch(current_epoch + lock_epochs);
         require!(
             unlock_epoch > current_epoch,
             "Unlock epoch must be greater than the current epoch"
         );
 
-        let output_tokens = self.update_energy(&user, |energy: &mut Energy<Self::Api>| {
-            self.extend_new_token_period(payment.clone(), unlock_epoch, current_epoch, energy)
+        let output_tokens = self.update_energy(&user, |energy: &mut UserEnergy<Self::Api>| {
+            self.extend_new_token_period(payment.clone(), lock_epochs, unlock_epoch, energy)
         });
 
         self.send().esdt_local_burn(
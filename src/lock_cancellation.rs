@@ -0,0 +1,170 @@
+multiversx_sc::imports!();
+multiversx_sc::derive_imports!();
+
+use common_structs::Epoch;
+use simple_lock::locked_token::LockedTokenAttributes;
+
+use crate::unlock_with_penalty::TOKEN_CAN_BE_UNLOCKED_ALREADY_ERR_MSG;
+
+pub const MAX_LOCK_CANCELLATION_GRACE_PERIOD: Epoch = 30;
+
+/// The amount of base asset a user locked into a nonce,
+/// starting with their first lock into it that is still within the grace period
+#[derive(TypeAbi, TopEncode, TopDecode, Clone, PartialEq, Debug)]
+pub struct CancellableLock<M: ManagedTypeApi> {
+    pub amount: BigUint<M>,
+    pub lock_epoch: Epoch,
+}
+
+#[multiversx_sc::module]
+pub trait LockCancellationModule:
+    simple_lock::basic_lock_unlock::BasicLockUnlock
+    + simple_lock::locked_token::LockedTokenModule
+    + simple_lock::token_attributes::TokenAttributesModule
+    + multiversx_sc_modules::default_issue_callbacks::DefaultIssueCallbacksModule
+    + crate::token_whitelist::TokenWhitelistModule
+    + crate::energy::EnergyModule
+    + crate::energy_checkpoint::EnergyCheckpointModule
+    + crate::energy_decay::EnergyDecayModule
+    + crate::energy_subscribers::EnergySubscribersModule
+    + crate::events::EventsModule
+    + multiversx_sc_modules::pause::PauseModule
+    + utils::UtilsModule
+{
+    /// Number of epochs after a lock during which the position can be cancelled penalty-free.
+    /// 0 disables cancellation.
+    #[only_owner]
+    #[endpoint(setLockCancellationGracePeriod)]
+    fn set_lock_cancellation_grace_period(&self, grace_period: Epoch) {
+        require!(
+            grace_period <= MAX_LOCK_CANCELLATION_GRACE_PERIOD,
+            "Invalid grace period"
+        );
+
+        self.lock_cancellation_grace_period().set(grace_period);
+    }
+
+    /// Cancels a lock within the grace period, returning the base asset without any penalty.
+    /// The energy gained from the lock is reverted.
+    ///
+    /// Only the amount the caller locked from the base asset into the nonce may be cancelled.
+    /// Locks made for another destination address are not cancellable by either of them.
+    /// Tokens received otherwise, e.g. by extending, merging or transfers, are not cancellable.
+    /// Since tokens with the same unlock epoch share the same nonce,
+    /// further locks into it within the grace period add to the cancellable amount,
+    /// while the grace period still starts at the first of them.
+    ///
+    /// Expected payments: LOCKED tokens
+    ///
+    /// Output payments: the originally locked tokens
+    #[payable("*")]
+    #[endpoint(cancelLock)]
+    fn cancel_lock(&self) -> EsdtTokenPayment {
+        self.require_not_paused();
+
+        let payment = self.call_value().single_esdt();
+        let locked_token_mapper = self.locked_token();
+        locked_token_mapper.require_same_token(&payment.token_identifier);
+
+        let caller = self.blockchain().get_caller();
+        let cancellable_amount = self.get_cancellable_amount(caller.clone(), payment.token_nonce);
+        require!(
+            payment.amount > 0 && payment.amount <= cancellable_amount,
+            "Lock may not be cancelled"
+        );
+
+        let cancellable_lock_mapper = self.cancellable_lock(&caller, payment.token_nonce);
+        if payment.amount == cancellable_amount {
+            cancellable_lock_mapper.clear();
+        } else {
+            cancellable_lock_mapper.update(|cancellable_lock| {
+                cancellable_lock.amount -= &payment.amount;
+            });
+        }
+
+        let attributes: LockedTokenAttributes<Self::Api> =
+            locked_token_mapper.get_token_attributes(payment.token_nonce);
+        let current_epoch = self.blockchain().get_block_epoch();
+        require!(
+            attributes.unlock_epoch > current_epoch,
+            TOKEN_CAN_BE_UNLOCKED_ALREADY_ERR_MSG
+        );
+
+        let energy_epoch = self.get_energy_epoch();
+        let mut energy = self.get_updated_user_energy(&caller);
+        energy.deplete_after_early_unlock(&payment.amount, attributes.unlock_epoch, energy_epoch);
+        self.set_user_energy(&caller, energy);
+
+        locked_token_mapper.nft_burn(payment.token_nonce, &payment.amount);
+        let unlocked_tokens =
+            self.to_esdt_payment(self.unlock_tokens_unchecked(payment, &attributes));
+        self.send().esdt_local_mint(
+            &unlocked_tokens.token_identifier,
+            0,
+            &unlocked_tokens.amount,
+        );
+        self.send().direct_esdt(
+            &caller,
+            &unlocked_tokens.token_identifier,
+            0,
+            &unlocked_tokens.amount,
+        );
+
+        unlocked_tokens
+    }
+
+    /// The amount of the given nonce the user may still cancel, 0 once the grace period passed
+    #[view(getCancellableAmount)]
+    fn get_cancellable_amount(&self, user: ManagedAddress, token_nonce: u64) -> BigUint {
+        let cancellable_lock_mapper = self.cancellable_lock(&user, token_nonce);
+        if cancellable_lock_mapper.is_empty() {
+            return BigUint::zero();
+        }
+
+        let cancellable_lock = cancellable_lock_mapper.get();
+        if self.is_within_lock_cancellation_grace_period(cancellable_lock.lock_epoch) {
+            cancellable_lock.amount
+        } else {
+            BigUint::zero()
+        }
+    }
+
+    fn is_within_lock_cancellation_grace_period(&self, lock_epoch: Epoch) -> bool {
+        let grace_period = self.lock_cancellation_grace_period().get();
+        if grace_period == 0 {
+            return false;
+        }
+
+        self.blockchain().get_block_epoch() <= lock_epoch + grace_period
+    }
+
+    /// Must only be called for tokens locked from the base asset
+    fn add_cancellable_lock(&self, user: &ManagedAddress, locked_tokens: &EsdtTokenPayment) {
+        let cancellable_lock_mapper = self.cancellable_lock(user, locked_tokens.token_nonce);
+        if !cancellable_lock_mapper.is_empty() {
+            let mut cancellable_lock = cancellable_lock_mapper.get();
+            if self.is_within_lock_cancellation_grace_period(cancellable_lock.lock_epoch) {
+                cancellable_lock.amount += &locked_tokens.amount;
+                cancellable_lock_mapper.set(&cancellable_lock);
+                return;
+            }
+        }
+
+        let current_epoch = self.blockchain().get_block_epoch();
+        cancellable_lock_mapper.set(&CancellableLock {
+            amount: locked_tokens.amount.clone(),
+            lock_epoch: current_epoch,
+        });
+    }
+
+    #[view(getLockCancellationGracePeriod)]
+    #[storage_mapper("lockCancellationGracePeriod")]
+    fn lock_cancellation_grace_period(&self) -> SingleValueMapper<Epoch>;
+
+    #[storage_mapper("cancellableLock")]
+    fn cancellable_lock(
+        &self,
+        user: &ManagedAddress,
+        token_nonce: u64,
+    ) -> SingleValueMapper<CancellableLock<Self::Api>>;
+}
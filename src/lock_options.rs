@@ -2,11 +2,25 @@ multiversx_sc::imports!();
 multiversx_sc::derive_imports!();
 
 use common_structs::Epoch;
-use unwrappable::Unwrappable;
 
 pub const EPOCHS_PER_MONTH: Epoch = 30;
 pub const EPOCHS_PER_YEAR: Epoch = 12 * EPOCHS_PER_MONTH;
 pub const MAX_PENALTY_PERCENTAGE: u64 = 10_000; // 100%
+pub const ENERGY_MULTIPLIER_PRECISION: u64 = 10_000; // 1x
+pub const MAX_UNLOCK_EPOCH_GRANULARITY: Epoch = EPOCHS_PER_YEAR;
+
+/// How unlock epochs are aligned to the unlock epoch granularity
+#[derive(
+    TypeAbi, TopEncode, TopDecode, NestedEncode, NestedDecode, Clone, Copy, PartialEq, Debug,
+)]
+pub enum UnlockEpochRoundingMode {
+    /// Round down to the start of the period (default)
+    Floor,
+    /// Round up to the start of the next period
+    Ceil,
+    /// Keep the exact unlock epoch
+    None,
+}
 
 #[derive(TypeAbi, TopEncode, TopDecode, NestedEncode, NestedDecode, Clone, Copy, Default)]
 pub struct LockOption {
@@ -14,48 +28,352 @@ pub struct LockOption {
     pub penalty_start_percentage: u64,
 }
 
-pub const MAX_LOCK_OPTIONS: usize = 10;
+pub const MAX_LOCK_OPTIONS: usize = 120;
 pub type AllLockOptions = ArrayVec<LockOption, MAX_LOCK_OPTIONS>;
 
+/// Options are always used for penalty calculation,
+/// but may only be chosen for new locks during their availability window, if any.
+/// Both window ends are inclusive.
+#[derive(
+    TypeAbi,
+    TopEncode,
+    TopDecode,
+    NestedEncode,
+    NestedDecode,
+    Clone,
+    Copy,
+    Default,
+    PartialEq,
+    Debug,
+)]
+pub struct LockOptionAvailability {
+    pub available_from_epoch: Option<Epoch>,
+    pub available_until_epoch: Option<Epoch>,
+}
+
+impl LockOptionAvailability {
+    pub fn is_available_at(&self, epoch: Epoch) -> bool {
+        if let Some(from_epoch) = self.available_from_epoch {
+            if epoch < from_epoch {
+                return false;
+            }
+        }
+        if let Some(until_epoch) = self.available_until_epoch {
+            if epoch > until_epoch {
+                return false;
+            }
+        }
+
+        true
+    }
+}
+
 #[multiversx_sc::module]
 pub trait LockOptionsModule {
-    fn get_lock_options(&self) -> AllLockOptions {
-        let options = self.lock_options().get();
-        require!(!options.is_empty(), "no lock options available");
+    fn require_has_lock_options(&self) {
+        require!(!self.lock_options().is_empty(), "no lock options available");
+    }
+
+    fn get_first_lock_option(&self) -> LockOption {
+        self.require_has_lock_options();
+
+        self.lock_options().get(1)
+    }
+
+    fn get_last_lock_option(&self) -> LockOption {
+        self.require_has_lock_options();
+
+        let lock_options_mapper = self.lock_options();
+        lock_options_mapper.get(lock_options_mapper.len())
+    }
+
+    /// Binary search over the sorted options.
+    /// Returns the index of the first option with at least `lock_epochs` epochs,
+    /// or `len + 1` if there is no such option. Indexes start at 1.
+    fn find_lock_option_lower_bound(&self, lock_epochs: Epoch) -> usize {
+        let lock_options_mapper = self.lock_options();
+        let mut low = 1;
+        let mut high = lock_options_mapper.len() + 1;
+        while low < high {
+            let mid = (low + high) / 2;
+            if lock_options_mapper.get(mid).lock_epochs < lock_epochs {
+                low = mid + 1;
+            } else {
+                high = mid;
+            }
+        }
+
+        low
+    }
+
+    fn find_lock_option_index(&self, lock_epochs: Epoch) -> Option<usize> {
+        let index = self.find_lock_option_lower_bound(lock_epochs);
+        let lock_options_mapper = self.lock_options();
+        if index <= lock_options_mapper.len()
+            && lock_options_mapper.get(index).lock_epochs == lock_epochs
+        {
+            Some(index)
+        } else {
+            None
+        }
+    }
 
-        options
+    fn find_lock_option(&self, lock_epochs: Epoch) -> Option<LockOption> {
+        self.find_lock_option_index(lock_epochs)
+            .map(|index| self.lock_options().get(index))
     }
 
+    /// Options outside their availability window may not be chosen.
+    /// In range mode, any duration between the shortest and the longest available option is accepted.
     fn require_is_listed_lock_option(&self, lock_epochs: Epoch) {
-        let lock_options = self.get_lock_options();
-        for option in &lock_options {
-            if option.lock_epochs == lock_epochs {
-                return;
+        self.require_has_lock_options();
+
+        let current_epoch = self.blockchain().get_block_epoch();
+        if self.lock_range_mode_enabled().get() {
+            require!(
+                self.is_in_lock_range(lock_epochs, current_epoch),
+                "Invalid lock choice"
+            );
+            return;
+        }
+
+        match self.find_lock_option(lock_epochs) {
+            Some(option) if self.is_lock_option_available(option.lock_epochs, current_epoch) => {}
+            _ => sc_panic!("Invalid lock choice"),
+        }
+    }
+
+    fn is_in_lock_range(&self, lock_epochs: Epoch, current_epoch: Epoch) -> bool {
+        match self.get_available_lock_range(current_epoch) {
+            Some((min_lock_epochs, max_lock_epochs)) => {
+                min_lock_epochs <= lock_epochs && lock_epochs <= max_lock_epochs
             }
+            None => false,
         }
+    }
 
-        sc_panic!("Invalid lock choice");
+    fn is_lock_option_available(&self, lock_epochs: Epoch, epoch: Epoch) -> bool {
+        self.get_lock_option_availability(lock_epochs)
+            .is_available_at(epoch)
     }
 
-    fn unlock_epoch_to_start_of_month(&self, unlock_epoch: Epoch) -> Epoch {
-        let extra_days = unlock_epoch % EPOCHS_PER_MONTH;
-        unlock_epoch - extra_days
+    /// Options without an availability window are always available
+    fn get_lock_option_availability(&self, lock_epochs: Epoch) -> LockOptionAvailability {
+        let availability_mapper = self.lock_option_availability(lock_epochs);
+        if !availability_mapper.is_empty() {
+            availability_mapper.get()
+        } else {
+            LockOptionAvailability::default()
+        }
     }
 
-    fn unlock_epoch_to_start_of_month_upper_estimate(&self, unlock_epoch: Epoch) -> Epoch {
-        let lower_bound_unlock = self.unlock_epoch_to_start_of_month(unlock_epoch);
+    /// The shortest and longest available options, if any.
+    /// Only looks past the shortest and longest options if they are outside their availability window
+    fn get_available_lock_range(&self, current_epoch: Epoch) -> Option<(Epoch, Epoch)> {
+        let lock_options_mapper = self.lock_options();
+        let len = lock_options_mapper.len();
+
+        let mut min_index = 1;
+        while min_index <= len
+            && !self.is_lock_option_available(
+                lock_options_mapper.get(min_index).lock_epochs,
+                current_epoch,
+            )
+        {
+            min_index += 1;
+        }
+        if min_index > len {
+            return None;
+        }
+
+        let mut max_index = len;
+        while !self.is_lock_option_available(
+            lock_options_mapper.get(max_index).lock_epochs,
+            current_epoch,
+        ) {
+            max_index -= 1;
+        }
+
+        let min_option = lock_options_mapper.get(min_index);
+        let max_option = lock_options_mapper.get(max_index);
+        Some((min_option.lock_epochs, max_option.lock_epochs))
+    }
+
+    /// Finds the lock period that lockTokens would round to the given unlock epoch.
+    /// If several options match, the longest one is used.
+    /// In range mode, the period is capped to the available range.
+    fn require_lock_period_for_unlock_epoch(&self, unlock_epoch: Epoch) -> Epoch {
+        self.require_has_lock_options();
+
+        let current_epoch = self.blockchain().get_block_epoch();
+        if self.lock_range_mode_enabled().get() {
+            let (min_lock_epochs, max_lock_epochs) =
+                match self.get_available_lock_range(current_epoch) {
+                    Some(lock_range) => lock_range,
+                    None => sc_panic!("Invalid lock choice"),
+                };
+            require!(
+                self.round_unlock_epoch(current_epoch + min_lock_epochs) <= unlock_epoch
+                    && unlock_epoch <= self.round_unlock_epoch(current_epoch + max_lock_epochs),
+                "Invalid lock choice"
+            );
+
+            let lock_epochs = unlock_epoch.saturating_sub(current_epoch);
+            return lock_epochs.clamp(min_lock_epochs, max_lock_epochs);
+        }
+
+        let mut opt_lock_epochs = None;
+        for option in self.lock_options().iter() {
+            let option_unlock_epoch = self.round_unlock_epoch(current_epoch + option.lock_epochs);
+            if option_unlock_epoch > unlock_epoch {
+                break;
+            }
+            if option_unlock_epoch == unlock_epoch
+                && self.is_lock_option_available(option.lock_epochs, current_epoch)
+            {
+                opt_lock_epochs = Some(option.lock_epochs);
+            }
+        }
+
+        match opt_lock_epochs {
+            Some(lock_epochs) => lock_epochs,
+            None => sc_panic!("Invalid lock choice"),
+        }
+    }
+
+    /// Switches to the scheduled options once their effective epoch is reached,
+    /// even before they are moved to the current options
+    fn lock_options(&self) -> VecMapper<Self::Api, LockOption> {
+        if self.is_scheduled_lock_options_change_active() {
+            self.scheduled_lock_options()
+        } else {
+            self.current_lock_options()
+        }
+    }
+
+    fn is_scheduled_lock_options_change_active(&self) -> bool {
+        let effective_epoch_mapper = self.scheduled_lock_options_epoch();
+        !effective_epoch_mapper.is_empty()
+            && self.blockchain().get_block_epoch() >= effective_epoch_mapper.get()
+    }
+
+    /// Must be called before modifying the current options.
+    /// Energy multipliers and availability windows of the options missing from the scheduled table
+    /// are cleared, as when removing options.
+    fn apply_scheduled_lock_options(&self) {
+        if !self.is_scheduled_lock_options_change_active() {
+            return;
+        }
+
+        let mut current_mapper = self.current_lock_options();
+        for option in current_mapper.iter() {
+            // only searches the scheduled options, as they are already active
+            if self.find_lock_option_index(option.lock_epochs).is_none() {
+                self.lock_option_energy_multiplier(option.lock_epochs)
+                    .clear();
+                self.lock_option_availability(option.lock_epochs).clear();
+            }
+        }
+
+        let mut scheduled_mapper = self.scheduled_lock_options();
+        current_mapper.clear();
+        for option in scheduled_mapper.iter() {
+            current_mapper.push(&option);
+        }
+        scheduled_mapper.clear();
+        self.scheduled_lock_options_epoch().clear();
+    }
+
+    /// Moves the options saved under `lockOptions` before they were stored in a sorted list.
+    /// They were already kept sorted.
+    fn migrate_legacy_lock_options(&self) {
+        let legacy_mapper = self.legacy_lock_options();
+        if legacy_mapper.is_empty() {
+            return;
+        }
+
+        let mut lock_options_mapper = self.current_lock_options();
+        for option in legacy_mapper.get().iter() {
+            lock_options_mapper.push(option);
+        }
+        legacy_mapper.clear();
+    }
+
+    /// Options without a multiplier set give 1x energy.
+    /// Range mode durations get the multiplier of the longest option not exceeding them.
+    fn get_energy_multiplier(&self, lock_epochs: Epoch) -> u64 {
+        let option_lock_epochs = match self.find_lock_option_index(lock_epochs) {
+            Some(_) => lock_epochs,
+            None => {
+                let next_index = self.find_lock_option_lower_bound(lock_epochs);
+                if next_index == 1 {
+                    return ENERGY_MULTIPLIER_PRECISION;
+                }
+
+                self.lock_options().get(next_index - 1).lock_epochs
+            }
+        };
+
+        let multiplier_mapper = self.lock_option_energy_multiplier(option_lock_epochs);
+        if !multiplier_mapper.is_empty() {
+            multiplier_mapper.get()
+        } else {
+            ENERGY_MULTIPLIER_PRECISION
+        }
+    }
+
+    fn get_weighted_lock_amount(&self, lock_amount: &BigUint, lock_epochs: Epoch) -> BigUint {
+        let multiplier = self.get_energy_multiplier(lock_epochs);
+
+        lock_amount * multiplier / ENERGY_MULTIPLIER_PRECISION
+    }
+
+    /// Defaults to EPOCHS_PER_MONTH
+    fn get_unlock_epoch_granularity(&self) -> Epoch {
+        let granularity_mapper = self.unlock_epoch_granularity();
+        if !granularity_mapper.is_empty() {
+            granularity_mapper.get()
+        } else {
+            EPOCHS_PER_MONTH
+        }
+    }
+
+    fn round_unlock_epoch(&self, unlock_epoch: Epoch) -> Epoch {
+        let granularity = self.get_unlock_epoch_granularity();
+        let extra_days = unlock_epoch % granularity;
+        match self.unlock_epoch_rounding_mode().get() {
+            UnlockEpochRoundingMode::Floor => unlock_epoch - extra_days,
+            UnlockEpochRoundingMode::Ceil => {
+                if extra_days == 0 {
+                    unlock_epoch
+                } else {
+                    unlock_epoch - extra_days + granularity
+                }
+            }
+            UnlockEpochRoundingMode::None => unlock_epoch,
+        }
+    }
+
+    /// Rounds up to the start of the next period, unless that would exceed the max lock option.
+    /// Used for computed unlock epochs (i.e. merging and migration), regardless of floor/ceil mode.
+    fn round_unlock_epoch_upper_estimate(&self, unlock_epoch: Epoch) -> Epoch {
+        if self.unlock_epoch_rounding_mode().get() == UnlockEpochRoundingMode::None {
+            return unlock_epoch;
+        }
+
+        let granularity = self.get_unlock_epoch_granularity();
+        let lower_bound_unlock = unlock_epoch - unlock_epoch % granularity;
         if unlock_epoch == lower_bound_unlock {
             return lower_bound_unlock;
         }
-        let new_unlock_epoch = lower_bound_unlock + EPOCHS_PER_MONTH;
+        let new_unlock_epoch = lower_bound_unlock + granularity;
         let current_epoch = self.blockchain().get_block_epoch();
         if current_epoch >= new_unlock_epoch {
             return new_unlock_epoch;
         }
 
         let new_lock_epochs_unbounded = new_unlock_epoch - current_epoch;
-        let lock_options = self.get_lock_options();
-        let last_lock_option = lock_options.last().unwrap_or_panic::<Self::Api>();
+        let last_lock_option = self.get_last_lock_option();
         if new_lock_epochs_unbounded <= last_lock_option.lock_epochs {
             new_unlock_epoch
         } else {
@@ -63,6 +381,43 @@ pub trait LockOptionsModule {
         }
     }
 
+    #[storage_mapper("unlockEpochGranularity")]
+    fn unlock_epoch_granularity(&self) -> SingleValueMapper<Epoch>;
+
+    #[view(getUnlockEpochRoundingMode)]
+    #[storage_mapper("unlockEpochRoundingMode")]
+    fn unlock_epoch_rounding_mode(&self) -> SingleValueMapper<UnlockEpochRoundingMode>;
+
+    #[view(isLockRangeModeEnabled)]
+    #[storage_mapper("lockRangeModeEnabled")]
+    fn lock_range_mode_enabled(&self) -> SingleValueMapper<bool>;
+
+    /// Sorted by lock epochs
+    #[storage_mapper("sortedLockOptions")]
+    fn current_lock_options(&self) -> VecMapper<LockOption>;
+
+    /// Sorted by lock epochs. Replaces the current options at the scheduled epoch.
+    #[storage_mapper("scheduledLockOptions")]
+    fn scheduled_lock_options(&self) -> VecMapper<LockOption>;
+
+    #[storage_mapper("scheduledLockOptionsEpoch")]
+    fn scheduled_lock_options_epoch(&self) -> SingleValueMapper<Epoch>;
+
+    #[storage_mapper("lockOptionsScheduleDelay")]
+    fn lock_options_schedule_delay(&self) -> SingleValueMapper<Epoch>;
+
     #[storage_mapper("lockOptions")]
-    fn lock_options(&self) -> SingleValueMapper<AllLockOptions>;
+    fn legacy_lock_options(&self) -> SingleValueMapper<AllLockOptions>;
+
+    #[storage_mapper("minMigratedTokenLockedPeriod")]
+    fn min_migrated_token_locked_period(&self) -> SingleValueMapper<Epoch>;
+
+    #[storage_mapper("lockOptionEnergyMultiplier")]
+    fn lock_option_energy_multiplier(&self, lock_epochs: Epoch) -> SingleValueMapper<u64>;
+
+    #[storage_mapper("lockOptionAvailability")]
+    fn lock_option_availability(
+        &self,
+        lock_epochs: Epoch,
+    ) -> SingleValueMapper<LockOptionAvailability>;
 }
@@ -3,9 +3,14 @@ multiversx_sc::imports!();
 use common_structs::{Epoch, Percent};
 
 use crate::lock_options::{
-    AllLockOptions, LockOption, EPOCHS_PER_YEAR, MAX_LOCK_OPTIONS, MAX_PENALTY_PERCENTAGE,
+    AllLockOptions, LockOption, LockOptionAvailability, UnlockEpochRoundingMode,
+    ENERGY_MULTIPLIER_PRECISION, EPOCHS_PER_YEAR, MAX_LOCK_OPTIONS, MAX_PENALTY_PERCENTAGE,
+    MAX_UNLOCK_EPOCH_GRANULARITY,
 };
 
+pub const MAX_ENERGY_MULTIPLIER: u64 = 10 * ENERGY_MULTIPLIER_PRECISION;
+pub const MIN_LOCK_OPTIONS_SCHEDULE_DELAY: Epoch = 7;
+
 #[multiversx_sc::module]
 pub trait LockOptionsEndpointsModule: crate::lock_options::LockOptionsModule {
     /// Add lock options, as pairs of epochs and penalty percentages.
@@ -22,63 +27,389 @@ pub trait LockOptionsEndpointsModule: crate::lock_options::LockOptionsModule {
     #[only_owner]
     #[endpoint(addLockOptions)]
     fn add_lock_options(&self, new_lock_options: MultiValueEncoded<MultiValue2<Epoch, Percent>>) {
-        self.lock_options().update(|options| {
-            let new_total_options = options.len() + new_lock_options.len();
+        self.apply_scheduled_lock_options();
+
+        let new_total_options = self.lock_options().len() + new_lock_options.len();
+        require!(
+            new_total_options <= MAX_LOCK_OPTIONS,
+            "Too many lock options"
+        );
+
+        for pair in new_lock_options {
+            let (lock_epochs, penalty_start_percentage) = pair.into_tuple();
             require!(
-                new_total_options <= MAX_LOCK_OPTIONS,
-                "Too many lock options"
+                lock_epochs >= EPOCHS_PER_YEAR
+                    && penalty_start_percentage <= MAX_PENALTY_PERCENTAGE,
+                "Invalid option"
             );
 
-            for pair in new_lock_options {
-                let (lock_epochs, penalty_start_percentage) = pair.into_tuple();
-                require!(
-                    lock_epochs >= EPOCHS_PER_YEAR
-                        && penalty_start_percentage <= MAX_PENALTY_PERCENTAGE,
-                    "Invalid option"
-                );
-
-                unsafe {
-                    options.push_unchecked(LockOption {
-                        lock_epochs,
-                        penalty_start_percentage,
-                    });
-                }
+            self.insert_lock_option(LockOption {
+                lock_epochs,
+                penalty_start_percentage,
+            });
+        }
+    }
+
+    /// Removes the given lock options. At least one option must remain,
+    /// and the max option may not go below the min lock period of migrated tokens.
+    ///
+    /// Existing tokens locked for longer than the new max option are not affected,
+    /// except that they incur the max option's penalty on early unlock.
+    #[only_owner]
+    #[endpoint(removeLockOptions)]
+    fn remove_lock_options(&self, lock_epochs_to_remove: MultiValueEncoded<Epoch>) {
+        self.apply_scheduled_lock_options();
+
+        let mut lock_options_mapper = self.lock_options();
+        for lock_epochs in lock_epochs_to_remove {
+            let index = self.require_lock_option_index(lock_epochs);
+
+            // shift the following options to keep the list sorted
+            let len = lock_options_mapper.len();
+            for i in index..len {
+                lock_options_mapper.set(i, &lock_options_mapper.get(i + 1));
             }
+            lock_options_mapper.swap_remove(len);
+
+            self.lock_option_energy_multiplier(lock_epochs).clear();
+            self.lock_option_availability(lock_epochs).clear();
+        }
+
+        require!(
+            !lock_options_mapper.is_empty(),
+            "Must keep at least one lock option"
+        );
 
-            sort_lock_options(options);
-            require_no_duplicate_lock_epoch_options::<Self::Api>(options);
-            require_valid_percentages::<Self::Api>(options);
-        });
+        self.require_valid_min_migrated_token_locked_period();
+    }
+
+    /// Updates the penalty percentage of a listed lock option.
+    /// Percentages must still increase as lock period increases.
+    #[only_owner]
+    #[endpoint(updateLockOptionPenalty)]
+    fn update_lock_option_penalty(&self, lock_epochs: Epoch, penalty_start_percentage: Percent) {
+        self.apply_scheduled_lock_options();
+
+        require!(
+            penalty_start_percentage <= MAX_PENALTY_PERCENTAGE,
+            "Invalid option"
+        );
+
+        let index = self.require_lock_option_index(lock_epochs);
+        self.require_valid_percentage_between(index - 1, penalty_start_percentage, index + 1);
+
+        let lock_options_mapper = self.lock_options();
+        let mut option = lock_options_mapper.get(index);
+        option.penalty_start_percentage = penalty_start_percentage;
+        lock_options_mapper.set(index, &option);
+    }
+
+    /// Restricts the epochs during which a lock option may be chosen for new locks.
+    /// Both ends are inclusive, and either may be left unset.
+    /// The option is still used for penalty calculation outside its window.
+    #[only_owner]
+    #[endpoint(setLockOptionAvailability)]
+    fn set_lock_option_availability(
+        &self,
+        lock_epochs: Epoch,
+        available_from_epoch: Option<Epoch>,
+        available_until_epoch: Option<Epoch>,
+    ) {
+        self.apply_scheduled_lock_options();
+
+        if let (Some(from_epoch), Some(until_epoch)) = (available_from_epoch, available_until_epoch)
+        {
+            require!(from_epoch <= until_epoch, "Invalid availability window");
+        }
+
+        self.require_lock_option_index(lock_epochs);
+        self.lock_option_availability(lock_epochs)
+            .set(LockOptionAvailability {
+                available_from_epoch,
+                available_until_epoch,
+            });
+    }
+
+    /// Schedules a new table of lock options, as pairs of epochs and penalty percentages,
+    /// which replaces all the current options at `effective_epoch`.
+    /// The effective epoch must be at least the schedule delay in the future,
+    /// so users are not surprised by penalty changes.
+    ///
+    /// Options must be sorted by lock epochs, with increasing percentages,
+    /// and follow the same rules as in addLockOptions. Options matching a current option's
+    /// lock epochs keep its energy multiplier and availability window.
+    /// Any previously scheduled table is replaced, and other changes made to the current options
+    /// until the effective epoch are discarded.
+    #[only_owner]
+    #[endpoint(scheduleLockOptions)]
+    fn schedule_lock_options(
+        &self,
+        effective_epoch: Epoch,
+        lock_options: MultiValueEncoded<MultiValue2<Epoch, Percent>>,
+    ) {
+        self.apply_scheduled_lock_options();
+
+        let current_epoch = self.blockchain().get_block_epoch();
+        require!(
+            effective_epoch >= current_epoch + self.get_lock_options_schedule_delay(),
+            "Effective epoch too early"
+        );
+        require!(
+            !lock_options.is_empty() && lock_options.len() <= MAX_LOCK_OPTIONS,
+            "Invalid number of lock options"
+        );
+
+        let mut scheduled_mapper = self.scheduled_lock_options();
+        scheduled_mapper.clear();
+
+        let mut prev_option = LockOption::default();
+        for pair in lock_options {
+            let (lock_epochs, penalty_start_percentage) = pair.into_tuple();
+            require!(
+                lock_epochs >= EPOCHS_PER_YEAR
+                    && penalty_start_percentage <= MAX_PENALTY_PERCENTAGE,
+                "Invalid option"
+            );
+            require!(
+                lock_epochs > prev_option.lock_epochs,
+                "Lock options must be sorted"
+            );
+            require!(
+                penalty_start_percentage > prev_option.penalty_start_percentage
+                    || scheduled_mapper.is_empty(),
+                "Invalid lock option percentages"
+            );
+
+            prev_option = LockOption {
+                lock_epochs,
+                penalty_start_percentage,
+            };
+            scheduled_mapper.push(&prev_option);
+        }
+
+        require!(
+            self.min_migrated_token_locked_period().get() <= prev_option.lock_epochs,
+            "Invalid min epoch for migrated token"
+        );
+
+        self.scheduled_lock_options_epoch().set(effective_epoch);
+    }
+
+    /// Cancels the scheduled lock options, which must not be active yet
+    #[only_owner]
+    #[endpoint(cancelScheduledLockOptions)]
+    fn cancel_scheduled_lock_options(&self) {
+        require!(
+            self.is_scheduled_lock_options_change_pending(),
+            "No scheduled lock options change"
+        );
+
+        self.scheduled_lock_options().clear();
+        self.scheduled_lock_options_epoch().clear();
+    }
+
+    /// Sets the min number of epochs between scheduling lock options and their effective epoch.
+    /// May not be lower than MIN_LOCK_OPTIONS_SCHEDULE_DELAY, which is also the default.
+    /// Only applies to tables scheduled afterwards.
+    #[only_owner]
+    #[endpoint(setLockOptionsScheduleDelay)]
+    fn set_lock_options_schedule_delay(&self, schedule_delay: Epoch) {
+        require!(
+            schedule_delay >= MIN_LOCK_OPTIONS_SCHEDULE_DELAY,
+            "Invalid schedule delay"
+        );
+
+        self.lock_options_schedule_delay().set(schedule_delay);
+    }
+
+    #[view(getLockOptionsScheduleDelay)]
+    fn get_lock_options_schedule_delay(&self) -> Epoch {
+        let schedule_delay_mapper = self.lock_options_schedule_delay();
+        if !schedule_delay_mapper.is_empty() {
+            schedule_delay_mapper.get()
+        } else {
+            MIN_LOCK_OPTIONS_SCHEDULE_DELAY
+        }
+    }
+
+    /// Sets the number of epochs unlock epochs are aligned to, e.g. 30 for months or 7 for weeks.
+    /// Only affects tokens locked afterwards.
+    #[only_owner]
+    #[endpoint(setUnlockEpochGranularity)]
+    fn set_unlock_epoch_granularity(&self, granularity: Epoch) {
+        require!(
+            granularity > 0 && granularity <= MAX_UNLOCK_EPOCH_GRANULARITY,
+            "Invalid unlock epoch granularity"
+        );
+
+        self.unlock_epoch_granularity().set(granularity);
+    }
+
+    #[view(getUnlockEpochGranularity)]
+    fn get_unlock_epoch_granularity_view(&self) -> Epoch {
+        self.get_unlock_epoch_granularity()
+    }
+
+    /// Sets how unlock epochs of new locks are aligned to the granularity:
+    /// - Floor: round down (default)
+    /// - Ceil: round up
+    /// - None: no rounding
+    #[only_owner]
+    #[endpoint(setUnlockEpochRoundingMode)]
+    fn set_unlock_epoch_rounding_mode(&self, rounding_mode: UnlockEpochRoundingMode) {
+        self.unlock_epoch_rounding_mode().set(rounding_mode);
+    }
+
+    /// In range mode, users may lock for any number of epochs
+    /// between the shortest and the longest available option, not only the exact options.
+    /// Penalties are interpolated between the surrounding options.
+    #[only_owner]
+    #[endpoint(setLockRangeMode)]
+    fn set_lock_range_mode(&self, enabled: bool) {
+        self.lock_range_mode_enabled().set(enabled);
+    }
+
+    /// Sets the energy multiplier for a listed lock option,
+    /// where 10_000 means 1x, i.e. one energy point per locked token per epoch.
+    /// For example, a multiplier of 12_000 gives 1.2x energy per epoch.
+    ///
+    /// Only affects tokens locked afterwards, existing positions keep their original weight.
+    #[only_owner]
+    #[endpoint(setLockOptionEnergyMultiplier)]
+    fn set_lock_option_energy_multiplier(&self, lock_epochs: Epoch, multiplier: u64) {
+        require!(
+            self.find_lock_option(lock_epochs).is_some(),
+            "Invalid lock choice"
+        );
+        require!(
+            multiplier > 0 && multiplier <= MAX_ENERGY_MULTIPLIER,
+            "Invalid energy multiplier"
+        );
+
+        self.lock_option_energy_multiplier(lock_epochs)
+            .set(multiplier);
+    }
+
+    #[view(getLockOptionEnergyMultiplier)]
+    fn get_lock_option_energy_multiplier(&self, lock_epochs: Epoch) -> u64 {
+        self.get_energy_multiplier(lock_epochs)
+    }
+
+    fn is_scheduled_lock_options_change_pending(&self) -> bool {
+        !self.scheduled_lock_options_epoch().is_empty()
+            && !self.is_scheduled_lock_options_change_active()
+    }
+
+    /// Keeps the list sorted. Percentages must be strictly increasing, without duplicate options.
+    fn insert_lock_option(&self, new_option: LockOption) {
+        let mut lock_options_mapper = self.lock_options();
+        let index = self.find_lock_option_lower_bound(new_option.lock_epochs);
+        let len = lock_options_mapper.len();
+        require!(
+            index > len || lock_options_mapper.get(index).lock_epochs != new_option.lock_epochs,
+            "Duplicate lock options"
+        );
+        self.require_valid_percentage_between(
+            index - 1,
+            new_option.penalty_start_percentage,
+            index,
+        );
+
+        // shift the following options to make room for the new one
+        lock_options_mapper.push(&new_option);
+        for i in (index + 1..=len + 1).rev() {
+            lock_options_mapper.set(i, &lock_options_mapper.get(i - 1));
+        }
+        lock_options_mapper.set(index, &new_option);
+    }
+
+    fn require_lock_option_index(&self, lock_epochs: Epoch) -> usize {
+        match self.find_lock_option_index(lock_epochs) {
+            Some(index) => index,
+            None => sc_panic!("Lock option not found"),
+        }
+    }
+
+    /// Indexes outside the list are ignored
+    fn require_valid_percentage_between(
+        &self,
+        prev_index: usize,
+        penalty_start_percentage: Percent,
+        next_index: usize,
+    ) {
+        let lock_options_mapper = self.lock_options();
+        if prev_index >= 1 {
+            require!(
+                lock_options_mapper.get(prev_index).penalty_start_percentage
+                    < penalty_start_percentage,
+                "Invalid lock option percentages"
+            );
+        }
+        if next_index <= lock_options_mapper.len() {
+            require!(
+                penalty_start_percentage
+                    < lock_options_mapper.get(next_index).penalty_start_percentage,
+                "Invalid lock option percentages"
+            );
+        }
+    }
+
+    fn require_valid_min_migrated_token_locked_period(&self) {
+        let max_lock_option = self.get_last_lock_option();
+        require!(
+            self.min_migrated_token_locked_period().get() <= max_lock_option.lock_epochs,
+            "Invalid min epoch for migrated token"
+        );
     }
 
     #[view(getLockOptions)]
     fn get_lock_options_view(&self) -> AllLockOptions {
-        self.lock_options().get()
+        let mut result = AllLockOptions::new();
+        for option in self.lock_options().iter() {
+            result.push(option);
+        }
+
+        result
     }
-}
 
-fn sort_lock_options(lock_options: &mut AllLockOptions) {
-    lock_options.sort_unstable_by(|first, second| first.lock_epochs.cmp(&second.lock_epochs));
-}
+    /// Returns the lock options, along with their availability window
+    /// and whether they may currently be chosen for new locks
+    #[view(getLockOptionsWithAvailability)]
+    fn get_lock_options_with_availability(
+        &self,
+    ) -> MultiValueEncoded<MultiValue3<LockOption, LockOptionAvailability, bool>> {
+        let current_epoch = self.blockchain().get_block_epoch();
+        let mut result = MultiValueEncoded::new();
+        for option in self.lock_options().iter() {
+            let availability = self.get_lock_option_availability(option.lock_epochs);
+            let is_available = availability.is_available_at(current_epoch);
+            result.push((option, availability, is_available).into());
+        }
+
+        result
+    }
 
-fn require_no_duplicate_lock_epoch_options<M: ManagedTypeApi>(lock_options: &AllLockOptions) {
-    let end_index = lock_options.len() - 1;
-    for i in 0..end_index {
-        let current_element = unsafe { lock_options.get_unchecked(i) };
-        let next_element = unsafe { lock_options.get_unchecked(i + 1) };
-        if current_element.lock_epochs == next_element.lock_epochs {
-            M::error_api_impl().signal_error(b"Duplicate lock options");
+    /// Returns the epoch at which the scheduled lock options replace the current ones,
+    /// if a change is pending
+    #[view(getScheduledLockOptionsEpoch)]
+    fn get_scheduled_lock_options_epoch(&self) -> Option<Epoch> {
+        if self.is_scheduled_lock_options_change_pending() {
+            Some(self.scheduled_lock_options_epoch().get())
+        } else {
+            None
         }
     }
-}
 
-fn require_valid_percentages<M: ManagedTypeApi>(lock_options: &AllLockOptions) {
-    let end_index = lock_options.len() - 1;
-    for i in 0..end_index {
-        let current_element = unsafe { lock_options.get_unchecked(i) };
-        let next_element = unsafe { lock_options.get_unchecked(i + 1) };
-        if current_element.penalty_start_percentage >= next_element.penalty_start_percentage {
-            M::error_api_impl().signal_error(b"Invalid lock option percentages");
+    /// Returns the pending lock options, if any
+    #[view(getScheduledLockOptions)]
+    fn get_scheduled_lock_options_view(&self) -> MultiValueEncoded<LockOption> {
+        let mut result = MultiValueEncoded::new();
+        if self.is_scheduled_lock_options_change_pending() {
+            for option in self.scheduled_lock_options().iter() {
+                result.push(option);
+            }
         }
+
+        result
     }
 }
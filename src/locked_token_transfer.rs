@@ -6,6 +6,9 @@ use crate::energy::Energy;
 pub trait LockedTokenTransferModule:
     utils::UtilsModule
     + crate::energy::EnergyModule
+    + crate::energy_checkpoint::EnergyCheckpointModule
+    + crate::energy_decay::EnergyDecayModule
+    + crate::energy_subscribers::EnergySubscribersModule
     + crate::events::EventsModule
     + multiversx_sc_modules::pause::PauseModule
 {
@@ -1,6 +1,6 @@
 multiversx_sc::imports!();
 
-use crate::energy::Energy;
+use crate::energy::{Energy, UserEnergy};
 use common_structs::{Epoch, UnlockEpochAmountPairs};
 use math::safe_sub;
 use simple_lock::error_messages::INVALID_PAYMENTS_ERR_MSG;
@@ -16,6 +16,9 @@ pub trait SimpleLockMigrationModule:
     + multiversx_sc_modules::default_issue_callbacks::DefaultIssueCallbacksModule
     + crate::token_whitelist::TokenWhitelistModule
     + crate::energy::EnergyModule
+    + crate::energy_checkpoint::EnergyCheckpointModule
+    + crate::energy_decay::EnergyDecayModule
+    + crate::energy_subscribers::EnergySubscribersModule
     + crate::events::EventsModule
     + crate::lock_options::LockOptionsModule
     + multiversx_sc_modules::pause::PauseModule
@@ -33,15 +36,33 @@ pub trait SimpleLockMigrationModule:
         self.require_paused();
 
         let user_updated_energy_mapper = self.user_updated_old_tokens_energy();
-        let current_epoch = self.blockchain().get_block_epoch();
+        let energy_epoch = self.get_energy_epoch();
         for user_energy in users_energy {
             let (user, total_locked_tokens, energy_amount) = user_energy.into_tuple();
-            let new_energy = Energy::new(energy_amount, current_epoch, total_locked_tokens);
-            self.set_energy_entry(&user, new_energy);
+            let new_energy = Energy::new(energy_amount, energy_epoch, total_locked_tokens);
+            self.set_user_energy(&user, UserEnergy::new(new_energy, ManagedVec::new()));
             user_updated_energy_mapper.add(&user);
         }
     }
 
+    /// Adds the energy of users whose entries were saved before the total energy existed.
+    /// The contract is paused on upgrade until all such users are added.
+    /// Users already included in the total are skipped.
+    #[only_owner]
+    #[endpoint(addUsersToTotalEnergy)]
+    fn add_users_to_total_energy(&self, users: MultiValueEncoded<ManagedAddress>) {
+        let zero_energy = UserEnergy::new_zero_energy(self.get_energy_epoch());
+        for user in users {
+            if !self.is_legacy_energy_entry(&user) {
+                continue;
+            }
+
+            let user_energy = self.get_updated_user_energy(&user);
+            self.update_total_energy(&zero_energy, &user_energy);
+            self.add_legacy_energy_checkpoint(&user);
+        }
+    }
+
     #[endpoint(updateEnergyAfterOldTokenUnlock)]
     fn update_energy_after_old_token_unlock(
         &self,
@@ -57,14 +78,14 @@ pub trait SimpleLockMigrationModule:
         self.require_caller_old_factory();
         self.require_old_tokens_energy_was_updated(&original_caller);
 
-        let current_epoch = self.blockchain().get_block_epoch();
+        let energy_epoch = self.get_energy_epoch();
 
-        self.update_energy(&original_caller, |energy: &mut Energy<Self::Api>| {
+        self.update_energy(&original_caller, |energy: &mut UserEnergy<Self::Api>| {
             for pair in initial_epoch_amount_pairs.pairs {
-                energy.update_after_unlock_any(&pair.amount, pair.epoch, current_epoch);
+                energy.update_after_unlock_any(&pair.amount, pair.epoch, energy_epoch);
             }
             for pair in final_epoch_amount_pairs.pairs {
-                energy.add_after_token_lock(&pair.amount, pair.epoch, current_epoch);
+                energy.add_after_token_lock(&pair.amount, pair.epoch, energy_epoch);
             }
         });
     }
@@ -113,7 +134,7 @@ pub trait SimpleLockMigrationModule:
         payment: EsdtTokenPayment,
         is_smart_contract_address: bool,
         current_epoch: Epoch,
-        energy: &mut Energy<Self::Api>,
+        energy: &mut UserEnergy<Self::Api>,
     ) -> EsdtTokenPayment {
         let attributes = self.decode_legacy_token(&payment.token_identifier, payment.token_nonce);
         self.send().esdt_local_burn(
@@ -125,19 +146,20 @@ pub trait SimpleLockMigrationModule:
         let unlock_epoch_amount_pairs = attributes.get_unlock_amounts_per_epoch(&payment.amount);
         let new_unlock_epoch = self
             .calculate_new_unlock_epoch_for_old_token(&unlock_epoch_amount_pairs, current_epoch);
+        let energy_epoch = self.get_energy_epoch();
         for epoch_amount_pair in unlock_epoch_amount_pairs.pairs {
             if is_smart_contract_address {
                 energy.add_after_token_lock(
                     &epoch_amount_pair.amount,
                     new_unlock_epoch,
-                    current_epoch,
+                    energy_epoch,
                 );
             } else {
                 energy.update_after_unlock_epoch_change(
                     &epoch_amount_pair.amount,
                     epoch_amount_pair.epoch,
                     new_unlock_epoch,
-                    current_epoch,
+                    energy_epoch,
                 );
             }
         }
@@ -168,15 +190,14 @@ pub trait SimpleLockMigrationModule:
             .unwrap_or_panic::<Self::Api>();
 
         let new_tentative_unlock_epoch = base_lock_epochs * TOKEN_MIGRATION_LOCK_EPOCHS_FACTOR;
-        let lock_options = self.get_lock_options();
-        let max_lock_option = lock_options.last().unwrap_or_panic::<Self::Api>();
+        let max_lock_option = self.get_last_lock_option();
         let mut new_unlock_epoch =
             core::cmp::min(new_tentative_unlock_epoch, max_lock_option.lock_epochs);
 
         let min_lock_period = self.min_migrated_token_locked_period().get();
         new_unlock_epoch = core::cmp::max(new_unlock_epoch, min_lock_period);
 
-        self.unlock_epoch_to_start_of_month_upper_estimate(current_epoch + new_unlock_epoch)
+        self.round_unlock_epoch_upper_estimate(current_epoch + new_unlock_epoch)
     }
 
     fn require_caller_old_factory(&self) {
@@ -198,9 +219,6 @@ pub trait SimpleLockMigrationModule:
     #[storage_mapper("oldLockedAssetFactoryAddress")]
     fn old_locked_asset_factory_address(&self) -> SingleValueMapper<ManagedAddress>;
 
-    #[storage_mapper("minMigratedTokenLockedPeriod")]
-    fn min_migrated_token_locked_period(&self) -> SingleValueMapper<Epoch>;
-
     #[storage_mapper("userUpdatedOldTokensEnergy")]
     fn user_updated_old_tokens_energy(&self) -> WhitelistMapper<ManagedAddress>;
 }
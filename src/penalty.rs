@@ -4,47 +4,115 @@ multiversx_sc::derive_imports!();
 use common_structs::{Epoch, Percent};
 use math::linear_interpolation;
 
-use crate::lock_options::LockOption;
+use crate::lock_options::{LockOption, MAX_PENALTY_PERCENTAGE};
+
+/// Internal scale of penalty percentages, used to avoid precision loss
+/// when chaining lock period reductions. Lock options are still configured in basis points.
+pub const PENALTY_PRECISION: u64 = 1_000_000_000_000; // 100%
+pub const CURVE_PRECISION: u64 = 1_000_000_000;
+pub const EXPONENTIAL_CURVE_HALF_LIVES: u64 = 4;
+
+/// Shape of the penalty between two consecutive lock options.
+/// All curves go through both options' penalties and are non-decreasing between them.
+#[derive(
+    TypeAbi, TopEncode, TopDecode, NestedEncode, NestedDecode, Clone, Copy, PartialEq, Debug,
+)]
+pub enum PenaltyCurve {
+    /// Straight line between the options (default)
+    Linear,
+    /// Convex: the penalty grows slowly after the shorter option, and faster towards the longer one
+    Quadratic,
+    /// Concave: the penalty grows fast after the shorter option,
+    /// and the remaining difference halves every quarter of the interval
+    Exponential,
+}
 
 #[multiversx_sc::module]
 pub trait LocalPenaltyModule: crate::lock_options::LockOptionsModule {
-    fn calculate_penalty_percentage_full_unlock(&self, lock_epochs_remaining: Epoch) -> Percent {
-        let lock_options = self.get_lock_options();
-        let last_index = lock_options.len() - 1;
-        let last_lock_option = unsafe { lock_options.get_unchecked(last_index) };
-        require!(
-            lock_epochs_remaining <= last_lock_option.lock_epochs,
-            "Invalid lock epochs"
-        );
-
-        let mut prev_option = LockOption::default();
-        let mut next_option = LockOption::default();
-
-        let first_index = 0;
-        let first_lock_option = unsafe { lock_options.get_unchecked(first_index) };
-        if last_index > 0 && lock_epochs_remaining > first_lock_option.lock_epochs {
-            for i in first_index..last_index {
-                let prev_option_temp = unsafe { lock_options.get_unchecked(i) };
-                let next_option_temp = unsafe { lock_options.get_unchecked(i + 1) };
-                if prev_option_temp.lock_epochs <= lock_epochs_remaining
-                    && lock_epochs_remaining <= next_option_temp.lock_epochs
-                {
-                    prev_option = *prev_option_temp;
-                    next_option = *next_option_temp;
-                    break;
-                }
-            }
+    #[only_owner]
+    #[endpoint(setPenaltyCurve)]
+    fn set_penalty_curve(&self, penalty_curve: PenaltyCurve) {
+        self.penalty_curve().set(penalty_curve);
+    }
+
+    /// Tokens locked for longer than the current max option (i.e. locked before that option was removed)
+    /// incur the max option's penalty
+    ///
+    /// The result is non-decreasing in `lock_epochs_remaining` and never exceeds the max option's penalty,
+    /// which keeps the partial unlock penalty monotonic and within PENALTY_PRECISION
+    ///
+    /// The result is scaled by PENALTY_PRECISION
+    fn calculate_penalty_percentage_full_unlock(&self, lock_epochs_remaining: Epoch) -> u64 {
+        let last_lock_option = self.get_last_lock_option();
+        let lock_epochs_remaining =
+            core::cmp::min(lock_epochs_remaining, last_lock_option.lock_epochs);
+
+        let lock_options_mapper = self.lock_options();
+        let next_index = self.find_lock_option_lower_bound(lock_epochs_remaining);
+        let next_option = lock_options_mapper.get(next_index);
+        let prev_option = if next_index > 1 {
+            lock_options_mapper.get(next_index - 1)
         } else {
-            // previous entry remains at the default of 0 penalty for 0 epochs
-            next_option = *first_lock_option;
-        }
+            // 0 penalty for 0 epochs
+            LockOption::default()
+        };
+
+        let prev_penalty = to_penalty_precision(prev_option.penalty_start_percentage);
+        let next_penalty = to_penalty_precision(next_option.penalty_start_percentage);
+        match self.penalty_curve().get() {
+            PenaltyCurve::Linear => linear_interpolation::<Self::Api, u128>(
+                prev_option.lock_epochs as u128,
+                next_option.lock_epochs as u128,
+                lock_epochs_remaining as u128,
+                prev_penalty as u128,
+                next_penalty as u128,
+            ) as u64,
+            curve => {
+                let progress = (lock_epochs_remaining - prev_option.lock_epochs) * CURVE_PRECISION
+                    / (next_option.lock_epochs - prev_option.lock_epochs);
+                let curve_progress = match curve {
+                    PenaltyCurve::Quadratic => quadratic_curve(progress),
+                    _ => exponential_curve(progress),
+                };
+                let penalty_diff = (next_penalty - prev_penalty) as u128;
 
-        linear_interpolation::<Self::Api, _>(
-            prev_option.lock_epochs,
-            next_option.lock_epochs,
-            lock_epochs_remaining,
-            prev_option.penalty_start_percentage,
-            next_option.penalty_start_percentage,
-        )
+                prev_penalty
+                    + (penalty_diff * curve_progress as u128 / CURVE_PRECISION as u128) as u64
+            }
+        }
     }
+
+    #[view(getPenaltyCurve)]
+    #[storage_mapper("penaltyCurve")]
+    fn penalty_curve(&self) -> SingleValueMapper<PenaltyCurve>;
+}
+
+/// Converts a percentage in basis points to PENALTY_PRECISION
+pub fn to_penalty_precision(percentage: Percent) -> u64 {
+    percentage * (PENALTY_PRECISION / MAX_PENALTY_PERCENTAGE)
+}
+
+/// Both input and output are in [0, CURVE_PRECISION]
+fn quadratic_curve(progress: u64) -> u64 {
+    progress * progress / CURVE_PRECISION
+}
+
+/// (1 - 2^(-k * x)) / (1 - 2^(-k)), with k = EXPONENTIAL_CURVE_HALF_LIVES.
+/// Both input and output are in [0, CURVE_PRECISION]
+fn exponential_curve(progress: u64) -> u64 {
+    let decay_at_end = inverse_power_of_two(EXPONENTIAL_CURVE_HALF_LIVES * CURVE_PRECISION);
+    let decay = inverse_power_of_two(EXPONENTIAL_CURVE_HALF_LIVES * progress);
+
+    (CURVE_PRECISION - decay) * CURVE_PRECISION / (CURVE_PRECISION - decay_at_end)
+}
+
+/// 2^(-exponent), with both exponent and result scaled by CURVE_PRECISION.
+/// Fractional exponents are linearly interpolated between the surrounding integer ones,
+/// so the result is exact for integers and strictly decreasing.
+fn inverse_power_of_two(exponent: u64) -> u64 {
+    let integer_part = exponent / CURVE_PRECISION;
+    let fractional_part = exponent % CURVE_PRECISION;
+    let lower_power = CURVE_PRECISION >> integer_part;
+
+    lower_power - lower_power * fractional_part / (2 * CURVE_PRECISION)
 }
@@ -0,0 +1,77 @@
+multiversx_sc::imports!();
+
+use common_structs::Epoch;
+
+use crate::energy_decay::EPOCHS_PER_WEEK;
+
+#[multiversx_sc::module]
+pub trait PenaltyAccountingModule: crate::events::EventsModule {
+    /// Penalties are counted in locked tokens, regardless of where they are sent afterwards.
+    /// Must only be called once the penalty can no longer be given back to the user.
+    fn record_penalty(
+        &self,
+        user: &ManagedAddress,
+        amount: &BigUint,
+        prev_lock_epochs: Epoch,
+        new_lock_epochs: Epoch,
+    ) {
+        if amount == &0 {
+            return;
+        }
+
+        let current_epoch = self.blockchain().get_block_epoch();
+        self.total_penalties_collected()
+            .update(|total| *total += amount);
+        self.penalties_collected_for_epoch(current_epoch)
+            .update(|total| *total += amount);
+        self.penalties_collected_for_week(get_week_for_epoch(current_epoch))
+            .update(|total| *total += amount);
+
+        self.emit_penalty_collected_event(user, amount.clone(), prev_lock_epochs, new_lock_epochs);
+    }
+
+    /// Total penalties collected between the two epochs, both inclusive.
+    /// Weeks start at epochs divisible by 7.
+    ///
+    /// Early unlock penalties are only counted if distributed by this SC,
+    /// as the unstake SC gives them back when the unbond is cancelled.
+    #[view(getPenaltiesCollected)]
+    fn get_penalties_collected(&self, from_epoch: Epoch, to_epoch: Epoch) -> BigUint {
+        require!(from_epoch <= to_epoch, "Invalid epoch range");
+
+        let mut total = BigUint::zero();
+        let mut epoch = from_epoch;
+        while epoch <= to_epoch {
+            let week_start_epoch = epoch - epoch % EPOCHS_PER_WEEK;
+            let is_full_week_in_range =
+                epoch == week_start_epoch && to_epoch - epoch >= EPOCHS_PER_WEEK - 1;
+            if is_full_week_in_range {
+                total += self
+                    .penalties_collected_for_week(get_week_for_epoch(epoch))
+                    .get();
+                epoch += EPOCHS_PER_WEEK;
+            } else {
+                total += self.penalties_collected_for_epoch(epoch).get();
+                epoch += 1;
+            }
+        }
+
+        total
+    }
+
+    #[view(getTotalPenaltiesCollected)]
+    #[storage_mapper("totalPenaltiesCollected")]
+    fn total_penalties_collected(&self) -> SingleValueMapper<BigUint>;
+
+    #[view(getPenaltiesCollectedForEpoch)]
+    #[storage_mapper("penaltiesCollectedForEpoch")]
+    fn penalties_collected_for_epoch(&self, epoch: Epoch) -> SingleValueMapper<BigUint>;
+
+    #[view(getPenaltiesCollectedForWeek)]
+    #[storage_mapper("penaltiesCollectedForWeek")]
+    fn penalties_collected_for_week(&self, week: u64) -> SingleValueMapper<BigUint>;
+}
+
+pub fn get_week_for_epoch(epoch: Epoch) -> u64 {
+    epoch / EPOCHS_PER_WEEK
+}
@@ -0,0 +1,107 @@
+multiversx_sc::imports!();
+multiversx_sc::derive_imports!();
+
+pub const PENALTY_DISCOUNT_PRECISION: u64 = 10_000;
+pub const MAX_PENALTY_DISCOUNT: u64 = 5_000; // 50%
+pub const MAX_PENALTY_DISCOUNT_TIERS: usize = 10;
+
+#[derive(
+    TypeAbi,
+    ManagedVecItem,
+    TopEncode,
+    TopDecode,
+    NestedEncode,
+    NestedDecode,
+    Clone,
+    PartialEq,
+    Debug,
+)]
+pub struct PenaltyDiscountTier<M: ManagedTypeApi> {
+    pub min_energy: BigUint<M>,
+    pub discount: u64,
+}
+
+pub type PenaltyDiscountTiers<M> = ManagedVec<M, PenaltyDiscountTier<M>>;
+
+#[multiversx_sc::module]
+pub trait PenaltyDiscountModule:
+    crate::energy::EnergyModule
+    + crate::energy_checkpoint::EnergyCheckpointModule
+    + crate::energy_decay::EnergyDecayModule
+    + crate::energy_subscribers::EnergySubscribersModule
+    + crate::events::EventsModule
+{
+    /// Replaces the penalty discount tiers, as pairs of min energy and discount in basis points.
+    /// Users with at least `min_energy` energy left after an early unlock
+    /// get its penalty reduced by the tier's discount, of at most MAX_PENALTY_DISCOUNT.
+    /// Both the thresholds and the discounts must be strictly increasing.
+    /// An empty list removes all discounts.
+    #[only_owner]
+    #[endpoint(setPenaltyDiscountTiers)]
+    fn set_penalty_discount_tiers(&self, tiers: MultiValueEncoded<MultiValue2<BigUint, u64>>) {
+        require!(
+            tiers.len() <= MAX_PENALTY_DISCOUNT_TIERS,
+            "Too many penalty discount tiers"
+        );
+
+        let mut new_tiers = PenaltyDiscountTiers::new();
+        for pair in tiers {
+            let (min_energy, discount) = pair.into_tuple();
+            require!(
+                discount > 0 && discount <= MAX_PENALTY_DISCOUNT,
+                "Invalid penalty discount"
+            );
+
+            if !new_tiers.is_empty() {
+                let prev_tier = new_tiers.get(new_tiers.len() - 1);
+                require!(
+                    prev_tier.min_energy < min_energy && prev_tier.discount < discount,
+                    "Penalty discount tiers must be increasing"
+                );
+            }
+
+            new_tiers.push(PenaltyDiscountTier {
+                min_energy,
+                discount,
+            });
+        }
+
+        self.penalty_discount_tiers().set(&new_tiers);
+    }
+
+    /// Discount in basis points, based on the user's current energy
+    #[view(getPenaltyDiscountForUser)]
+    fn get_penalty_discount_for_user(&self, user: ManagedAddress) -> u64 {
+        let energy_amount = self.get_energy_amount_for_user(user);
+
+        self.get_penalty_discount(&energy_amount)
+    }
+
+    fn get_penalty_discount(&self, energy_amount: &BigUint) -> u64 {
+        let tiers = self.penalty_discount_tiers().get();
+        let mut discount = 0;
+        for tier in &tiers {
+            if *energy_amount < tier.min_energy {
+                break;
+            }
+
+            discount = tier.discount;
+        }
+
+        discount
+    }
+
+    /// The energy amount must not include the energy of the tokens the penalty is paid for
+    fn apply_penalty_discount(&self, penalty_amount: BigUint, energy_amount: &BigUint) -> BigUint {
+        let discount = self.get_penalty_discount(energy_amount);
+        if discount == 0 {
+            return penalty_amount;
+        }
+
+        penalty_amount * (PENALTY_DISCOUNT_PRECISION - discount) / PENALTY_DISCOUNT_PRECISION
+    }
+
+    #[view(getPenaltyDiscountTiers)]
+    #[storage_mapper("penaltyDiscountTiers")]
+    fn penalty_discount_tiers(&self) -> SingleValueMapper<PenaltyDiscountTiers<Self::Api>>;
+}
@@ -0,0 +1,206 @@
+multiversx_sc::imports!();
+multiversx_sc::derive_imports!();
+
+pub const TOTAL_PENALTY_SHARES: u64 = 10_000;
+
+mod fees_collector_proxy {
+    multiversx_sc::imports!();
+
+    #[multiversx_sc::proxy]
+    pub trait FeesCollectorProxy {
+        #[payable("*")]
+        #[endpoint(depositSwapFees)]
+        fn deposit_swap_fees(&self);
+    }
+}
+
+/// Shares of the penalty, in basis points, which must add up to TOTAL_PENALTY_SHARES
+#[derive(
+    TypeAbi, TopEncode, TopDecode, NestedEncode, NestedDecode, Clone, PartialEq, Debug, Default,
+)]
+pub struct PenaltyDistribution {
+    pub burn_shares: u64,
+    pub fees_collector_shares: u64,
+    pub treasury_shares: u64,
+}
+
+#[derive(
+    TypeAbi, TopEncode, TopDecode, NestedEncode, NestedDecode, Clone, Copy, PartialEq, Debug,
+)]
+pub enum PenaltyDestination {
+    Burn,
+    FeesCollector,
+    Treasury,
+}
+
+#[multiversx_sc::module]
+pub trait PenaltyDistributionModule:
+    crate::token_whitelist::TokenWhitelistModule + crate::events::EventsModule + utils::UtilsModule
+{
+    /// Splits penalties, in basis points, between burning the base asset,
+    /// the fees collector (through depositSwapFees) and the treasury.
+    /// While not set, penalties are handled by the unstake SC,
+    /// or burned if the internal unbond mode is set.
+    #[only_owner]
+    #[endpoint(setPenaltyDistribution)]
+    fn set_penalty_distribution(
+        &self,
+        burn_shares: u64,
+        fees_collector_shares: u64,
+        treasury_shares: u64,
+    ) {
+        require!(
+            burn_shares + fees_collector_shares + treasury_shares == TOTAL_PENALTY_SHARES,
+            "Invalid penalty distribution"
+        );
+        require!(
+            fees_collector_shares == 0 || !self.fees_collector_address().is_empty(),
+            "Fees collector address not set"
+        );
+        require!(
+            treasury_shares == 0 || !self.treasury_address().is_empty(),
+            "Treasury address not set"
+        );
+
+        self.penalty_distribution().set(PenaltyDistribution {
+            burn_shares,
+            fees_collector_shares,
+            treasury_shares,
+        });
+    }
+
+    /// Penalties go back to being handled by the unstake SC, or burned in the internal unbond mode
+    #[only_owner]
+    #[endpoint(clearPenaltyDistribution)]
+    fn clear_penalty_distribution(&self) {
+        self.penalty_distribution().clear();
+    }
+
+    #[only_owner]
+    #[endpoint(setFeesCollectorAddress)]
+    fn set_fees_collector_address(&self, sc_address: ManagedAddress) {
+        self.require_sc_address(&sc_address);
+        self.fees_collector_address().set(&sc_address);
+    }
+
+    #[only_owner]
+    #[endpoint(setTreasuryAddress)]
+    fn set_treasury_address(&self, address: ManagedAddress) {
+        require!(!address.is_zero(), "Invalid treasury address");
+        self.treasury_address().set(&address);
+    }
+
+    fn is_penalty_distribution_set(&self) -> bool {
+        !self.penalty_distribution().is_empty()
+    }
+
+    /// The penalties are paid in locked tokens, owned by the SC.
+    /// They are burned, and the base asset is minted for the fees collector and treasury shares
+    /// of their total. Rounding leftovers are burned, as is everything if no distribution is set.
+    fn distribute_penalty(&self, user: &ManagedAddress, penalties: &ManagedVec<EsdtTokenPayment>) {
+        let mut total_penalty = BigUint::zero();
+        for penalty in penalties {
+            if penalty.amount == 0 {
+                continue;
+            }
+
+            self.send().esdt_local_burn(
+                &penalty.token_identifier,
+                penalty.token_nonce,
+                &penalty.amount,
+            );
+            total_penalty += &penalty.amount;
+        }
+        if total_penalty == 0 {
+            return;
+        }
+
+        // no shares for the fees collector and treasury, so everything is burned
+        let distribution_mapper = self.penalty_distribution();
+        let distribution = if !distribution_mapper.is_empty() {
+            distribution_mapper.get()
+        } else {
+            PenaltyDistribution::default()
+        };
+        let fees_collector_amount =
+            &total_penalty * distribution.fees_collector_shares / TOTAL_PENALTY_SHARES;
+        let treasury_amount = &total_penalty * distribution.treasury_shares / TOTAL_PENALTY_SHARES;
+        let burn_amount = &total_penalty - &fees_collector_amount - &treasury_amount;
+
+        let base_asset_token_id = self.base_asset_token_id().get();
+        if fees_collector_amount > 0 {
+            self.send()
+                .esdt_local_mint(&base_asset_token_id, 0, &fees_collector_amount);
+
+            let fees_collector_address = self.fees_collector_address().get();
+            let _: IgnoreValue = self
+                .fees_collector_proxy_obj(fees_collector_address)
+                .deposit_swap_fees()
+                .with_esdt_transfer(EsdtTokenPayment::new(
+                    base_asset_token_id.clone(),
+                    0,
+                    fees_collector_amount.clone(),
+                ))
+                .execute_on_dest_context();
+        }
+        if treasury_amount > 0 {
+            self.send()
+                .esdt_local_mint(&base_asset_token_id, 0, &treasury_amount);
+            self.send().direct_esdt(
+                &self.treasury_address().get(),
+                &base_asset_token_id,
+                0,
+                &treasury_amount,
+            );
+        }
+
+        self.record_penalty_leg(user, PenaltyDestination::Burn, burn_amount);
+        self.record_penalty_leg(
+            user,
+            PenaltyDestination::FeesCollector,
+            fees_collector_amount,
+        );
+        self.record_penalty_leg(user, PenaltyDestination::Treasury, treasury_amount);
+    }
+
+    fn record_penalty_leg(
+        &self,
+        user: &ManagedAddress,
+        destination: PenaltyDestination,
+        amount: BigUint,
+    ) {
+        if amount == 0 {
+            return;
+        }
+
+        self.total_penalty_distributed(destination)
+            .update(|total| *total += &amount);
+        self.emit_penalty_distributed_event(user, destination, amount);
+    }
+
+    #[proxy]
+    fn fees_collector_proxy_obj(
+        &self,
+        sc_address: ManagedAddress,
+    ) -> fees_collector_proxy::Proxy<Self::Api>;
+
+    #[view(getPenaltyDistribution)]
+    #[storage_mapper("penaltyDistribution")]
+    fn penalty_distribution(&self) -> SingleValueMapper<PenaltyDistribution>;
+
+    #[view(getFeesCollectorAddress)]
+    #[storage_mapper("feesCollectorAddress")]
+    fn fees_collector_address(&self) -> SingleValueMapper<ManagedAddress>;
+
+    #[view(getTreasuryAddress)]
+    #[storage_mapper("treasuryAddress")]
+    fn treasury_address(&self) -> SingleValueMapper<ManagedAddress>;
+
+    /// Total base asset amount burned or sent to the given destination
+    #[view(getTotalPenaltyDistributed)]
+    #[storage_mapper("totalPenaltyDistributed")]
+    fn total_penalty_distributed(
+        &self,
+        destination: PenaltyDestination,
+    ) -> SingleValueMapper<BigUint>;
+}
@@ -9,7 +9,7 @@ use mergeable::Mergeable;
 use simple_lock::locked_token::LockedTokenAttributes;
 use unwrappable::Unwrappable;
 
-use crate::{energy::Energy, unlock_with_penalty::TOKEN_CAN_BE_UNLOCKED_ALREADY_ERR_MSG};
+use crate::{energy::UserEnergy, unlock_with_penalty::TOKEN_CAN_BE_UNLOCKED_ALREADY_ERR_MSG};
 
 #[derive(TopEncode, TopDecode, Clone, PartialEq, Debug)]
 pub struct LockedAmountWeightAttributesPair<M: ManagedTypeApi> {
@@ -61,6 +61,9 @@ pub trait TokenMergingModule:
     + multiversx_sc_modules::pause::PauseModule
     + crate::penalty::LocalPenaltyModule
     + crate::energy::EnergyModule
+    + crate::energy_checkpoint::EnergyCheckpointModule
+    + crate::energy_decay::EnergyDecayModule
+    + crate::energy_subscribers::EnergySubscribersModule
     + crate::events::EventsModule
     + crate::lock_options::LockOptionsModule
     + utils::UtilsModule
@@ -98,7 +101,7 @@ pub trait TokenMergingModule:
     fn merge_tokens(
         self,
         mut payments: PaymentsVec<Self::Api>,
-        energy: &mut Energy<Self::Api>,
+        energy: &mut UserEnergy<Self::Api>,
     ) -> LockedAmountWeightAttributesPair<Self::Api> {
         let locked_token_mapper = self.locked_token();
         locked_token_mapper.require_all_same_token(&payments);
@@ -114,10 +117,11 @@ pub trait TokenMergingModule:
             TOKEN_CAN_BE_UNLOCKED_ALREADY_ERR_MSG
         );
 
-        energy.update_after_unlock_any(
+        let energy_epoch = self.get_energy_epoch();
+        let mut weighted_amount = energy.update_after_unlock_any(
             &first_payment.amount,
             first_token_attributes.unlock_epoch,
-            current_epoch,
+            energy_epoch,
         );
 
         locked_token_mapper.nft_burn(first_payment.token_nonce, &first_payment.amount);
@@ -132,7 +136,11 @@ pub trait TokenMergingModule:
                 TOKEN_CAN_BE_UNLOCKED_ALREADY_ERR_MSG
             );
 
-            energy.update_after_unlock_any(&payment.amount, attributes.unlock_epoch, current_epoch);
+            weighted_amount += energy.update_after_unlock_any(
+                &payment.amount,
+                attributes.unlock_epoch,
+                energy_epoch,
+            );
 
             locked_token_mapper.nft_burn(payment.token_nonce, &payment.amount);
 
@@ -142,13 +150,15 @@ pub trait TokenMergingModule:
         }
 
         let normalized_unlock_epoch =
-            self.unlock_epoch_to_start_of_month_upper_estimate(output_pair.attributes.unlock_epoch);
+            self.round_unlock_epoch_upper_estimate(output_pair.attributes.unlock_epoch);
         output_pair.attributes.unlock_epoch = normalized_unlock_epoch;
 
-        energy.add_after_token_lock(
+        // merged tokens keep their original weights
+        energy.add_after_weighted_token_lock(
             &output_pair.token_amount,
+            &weighted_amount,
             output_pair.attributes.unlock_epoch,
-            current_epoch,
+            energy_epoch,
         );
 
         output_pair
@@ -0,0 +1,171 @@
+multiversx_sc::imports!();
+multiversx_sc::derive_imports!();
+
+use common_structs::Epoch;
+use simple_lock::locked_token::LockedTokenAttributes;
+
+pub const MAX_UNBOND_EPOCHS: Epoch = 30;
+
+#[derive(
+    TypeAbi, TopEncode, TopDecode, NestedEncode, NestedDecode, Clone, Copy, PartialEq, Debug,
+)]
+pub enum UnbondMode {
+    /// Early unlocked tokens are deposited in the token unstake SC (default)
+    ExternalSc,
+    /// Early unlocked tokens are kept in this SC until the unbond period has passed
+    Internal,
+}
+
+/// The locked tokens are held by the SC until the entry is claimed or cancelled
+#[derive(TypeAbi, TopEncode, TopDecode, NestedEncode, NestedDecode, Clone, PartialEq, Debug)]
+pub struct UnbondEntry<M: ManagedTypeApi> {
+    pub unlock_epoch: Epoch,
+    pub locked_tokens: EsdtTokenPayment<M>,
+    pub unlocked_amount: BigUint<M>,
+    pub weighted_amount: BigUint<M>,
+}
+
+#[multiversx_sc::module]
+pub trait UnbondModule:
+    simple_lock::locked_token::LockedTokenModule
+    + simple_lock::token_attributes::TokenAttributesModule
+    + multiversx_sc_modules::default_issue_callbacks::DefaultIssueCallbacksModule
+    + crate::token_whitelist::TokenWhitelistModule
+    + crate::energy::EnergyModule
+    + crate::energy_checkpoint::EnergyCheckpointModule
+    + crate::energy_decay::EnergyDecayModule
+    + crate::energy_subscribers::EnergySubscribersModule
+    + crate::events::EventsModule
+    + multiversx_sc_modules::pause::PauseModule
+    + utils::UtilsModule
+{
+    /// Switches between depositing early unlocked tokens in the token unstake SC
+    /// and keeping them in this SC for `unbondEpochs` epochs.
+    /// Already pending entries can still be claimed or cancelled after switching.
+    /// In the internal mode, penalties are always handled by this SC,
+    /// and burned if no penalty distribution is set.
+    #[only_owner]
+    #[endpoint(setUnbondMode)]
+    fn set_unbond_mode(&self, unbond_mode: UnbondMode) {
+        self.unbond_mode().set(unbond_mode);
+    }
+
+    #[only_owner]
+    #[endpoint(setUnbondEpochs)]
+    fn set_unbond_epochs(&self, unbond_epochs: Epoch) {
+        require!(unbond_epochs <= MAX_UNBOND_EPOCHS, "Invalid unbond epochs");
+
+        self.unbond_epochs().set(unbond_epochs);
+    }
+
+    /// Claims the base asset of all the entries whose unbond period has passed.
+    /// Their locked tokens are burned. Penalties were already taken out on unlockEarly.
+    ///
+    /// Output payments: the unlocked tokens
+    #[endpoint(claimUnbonded)]
+    fn claim_unbonded(&self) -> EsdtTokenPayment {
+        self.require_not_paused();
+
+        let caller = self.blockchain().get_caller();
+        let current_epoch = self.blockchain().get_block_epoch();
+        let mut entries_mapper = self.unbond_entries(&caller);
+        let mut unlocked_amount = BigUint::zero();
+        let mut index = 1;
+        while index <= entries_mapper.len() {
+            let entry = entries_mapper.get(index);
+            if entry.unlock_epoch > current_epoch {
+                index += 1;
+                continue;
+            }
+
+            self.send().esdt_local_burn(
+                &entry.locked_tokens.token_identifier,
+                entry.locked_tokens.token_nonce,
+                &entry.locked_tokens.amount,
+            );
+            unlocked_amount += entry.unlocked_amount;
+            entries_mapper.swap_remove(index);
+        }
+        require!(unlocked_amount > 0, "Nothing to claim");
+
+        let base_asset = self.base_asset_token_id().get();
+        self.send()
+            .esdt_local_mint(&base_asset, 0, &unlocked_amount);
+        self.send()
+            .direct_esdt(&caller, &base_asset, 0, &unlocked_amount);
+
+        EsdtTokenPayment::new(base_asset, 0, unlocked_amount)
+    }
+
+    /// Cancels all the pending entries, including the claimable ones.
+    /// The locked tokens are sent back, and their energy is restored.
+    /// Penalties are not given back.
+    ///
+    /// Output payments: the locked tokens
+    #[endpoint(cancelUnbond)]
+    fn cancel_unbond(&self) -> MultiValueEncoded<EsdtTokenPayment> {
+        self.require_not_paused();
+
+        let caller = self.blockchain().get_caller();
+        let mut entries_mapper = self.unbond_entries(&caller);
+        require!(!entries_mapper.is_empty(), "No pending unbond");
+
+        let locked_token_mapper = self.locked_token();
+        let energy_epoch = self.get_energy_epoch();
+        let mut energy = self.get_updated_user_energy(&caller);
+        let mut output_payments = ManagedVec::new();
+        for entry in entries_mapper.iter() {
+            let attributes: LockedTokenAttributes<Self::Api> =
+                locked_token_mapper.get_token_attributes(entry.locked_tokens.token_nonce);
+            energy.add_after_weighted_token_lock(
+                &entry.locked_tokens.amount,
+                &entry.weighted_amount,
+                attributes.unlock_epoch,
+                energy_epoch,
+            );
+
+            output_payments.push(entry.locked_tokens);
+        }
+        entries_mapper.clear();
+
+        self.set_user_energy(&caller, energy);
+        self.send().direct_multi(&caller, &output_payments);
+
+        output_payments.into()
+    }
+
+    fn is_internal_unbond_mode(&self) -> bool {
+        self.unbond_mode().get() == UnbondMode::Internal
+    }
+
+    /// The locked tokens must already be owned by the SC,
+    /// and their energy depleted from the user's entry
+    fn add_unbond_entry(
+        &self,
+        user: &ManagedAddress,
+        locked_tokens: EsdtTokenPayment,
+        unlocked_amount: BigUint,
+        weighted_amount: BigUint,
+    ) {
+        let current_epoch = self.blockchain().get_block_epoch();
+        let unlock_epoch = current_epoch + self.unbond_epochs().get();
+        self.unbond_entries(user).push(&UnbondEntry {
+            unlock_epoch,
+            locked_tokens,
+            unlocked_amount,
+            weighted_amount,
+        });
+    }
+
+    #[view(getUnbondMode)]
+    #[storage_mapper("unbondMode")]
+    fn unbond_mode(&self) -> SingleValueMapper<UnbondMode>;
+
+    #[view(getUnbondEpochs)]
+    #[storage_mapper("unbondEpochs")]
+    fn unbond_epochs(&self) -> SingleValueMapper<Epoch>;
+
+    #[view(getUnbondEntries)]
+    #[storage_mapper("unbondEntries")]
+    fn unbond_entries(&self, user: &ManagedAddress) -> VecMapper<UnbondEntry<Self::Api>>;
+}
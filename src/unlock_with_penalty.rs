@@ -2,17 +2,30 @@ multiversx_sc::imports!();
 multiversx_sc::derive_imports!();
 
 use common_structs::Epoch;
+use mergeable::Mergeable;
 
 use simple_lock::locked_token::LockedTokenAttributes;
 
-use crate::{energy::Energy, lock_options::MAX_PENALTY_PERCENTAGE};
+use crate::{energy::UserEnergy, penalty::PENALTY_PRECISION};
 
 pub static TOKEN_CAN_BE_UNLOCKED_ALREADY_ERR_MSG: &[u8] = b"Token can be unlocked already";
 
 pub struct LockReduceResult<M: ManagedTypeApi> {
+    pub prev_lock_epochs: u64,
     pub new_lock_epochs: u64,
+    pub penalty_amount: BigUint<M>,
     pub unlocked_tokens: EgldOrEsdtTokenPayment<M>,
-    pub energy: Energy<M>,
+    /// As removed from the energy entry
+    pub weighted_amount: BigUint<M>,
+}
+
+/// What unlockEarly or reduceLockPeriod would currently return for a user
+#[derive(TypeAbi, TopEncode, TopDecode, NestedEncode, NestedDecode, Clone, PartialEq, Debug)]
+pub struct LockReduceQuote<M: ManagedTypeApi> {
+    pub penalty_amount: BigUint<M>,
+    pub received_amount: BigUint<M>,
+    pub new_unlock_epoch: Epoch,
+    pub energy_delta: BigInt<M>,
 }
 
 #[multiversx_sc::module]
@@ -22,77 +35,225 @@ pub trait UnlockWithPenaltyModule:
     + simple_lock::token_attributes::TokenAttributesModule
     + multiversx_sc_modules::default_issue_callbacks::DefaultIssueCallbacksModule
     + crate::energy::EnergyModule
+    + crate::energy_checkpoint::EnergyCheckpointModule
+    + crate::energy_decay::EnergyDecayModule
+    + crate::energy_subscribers::EnergySubscribersModule
     + crate::lock_options::LockOptionsModule
     + crate::events::EventsModule
+    + crate::lock_cancellation::LockCancellationModule
     + multiversx_sc_modules::pause::PauseModule
     + crate::token_merging::TokenMergingModule
     + crate::penalty::LocalPenaltyModule
+    + crate::penalty_distribution::PenaltyDistributionModule
+    + crate::penalty_discount::PenaltyDiscountModule
+    + crate::penalty_accounting::PenaltyAccountingModule
     + crate::unstake::UnstakeModule
+    + crate::unbond::UnbondModule
     + utils::UtilsModule
     + sc_whitelist_module::SCWhitelistModule
     + crate::token_whitelist::TokenWhitelistModule
 {
-    /// Unlock a locked token instantly. This incures a penalty.
+    /// Unlock locked tokens instantly. This incures a penalty.
     /// The longer the remaining locking time, the bigger the penalty.
-    /// Tokens can be unlocked through another SC after the unbond period has passed.
+    /// Tokens can be unlocked through another SC after the unbond period has passed,
+    /// or through claimUnbonded if the internal unbond mode is set.
+    ///
+    /// Expected payments: any number of LOCKED tokens, with any nonces.
+    /// The penalty is computed separately for each of them,
+    /// and all of them go through a single deposit in the unstake SC.
+    /// For several nonces, the unstake SC's `depositUserTokens` must accept all of them at once.
     #[payable("*")]
     #[endpoint(unlockEarly)]
     fn unlock_early(&self) {
         self.require_not_paused();
         let caller = self.blockchain().get_caller();
-        let payment = self.call_value().single_esdt();
-        let reduce_result = self.reduce_lock_period_common(&caller, payment.clone(), None);
-
-        let unlocked_tokens = self.to_esdt_payment(reduce_result.unlocked_tokens);
-        self.send().esdt_local_mint(
-            &unlocked_tokens.token_identifier,
-            0,
-            &unlocked_tokens.amount,
-        );
+        let payments = self.get_non_empty_payments();
+        let is_penalty_handled_locally = self.is_penalty_handled_locally();
+        let is_internal_unbond_mode = self.is_internal_unbond_mode();
+        let current_epoch = self.blockchain().get_block_epoch();
+
+        let base_asset = self.base_asset_token_id().get();
+        let mut unlocked_tokens = EsdtTokenPayment::new(base_asset, 0, BigUint::zero());
+        let mut locked_tokens = ManagedVec::new();
+        let mut penalties = ManagedVec::new();
+        let mut energy = self.get_updated_user_energy(&caller);
+        for payment in &payments {
+            let reduce_result =
+                self.reduce_lock_period_common(payment.clone(), current_epoch, &mut energy);
+            let nonce_unlocked_tokens = self.to_esdt_payment(reduce_result.unlocked_tokens);
+
+            // the unstake SC only receives the penalty if it is not handled here.
+            // It then gives it back if the unbond is cancelled, so it is not recorded
+            let mut locked_payment = payment.clone();
+            if is_penalty_handled_locally {
+                self.record_penalty(
+                    &caller,
+                    &reduce_result.penalty_amount,
+                    reduce_result.prev_lock_epochs,
+                    0,
+                );
+                penalties.push(EsdtTokenPayment::new(
+                    locked_payment.token_identifier.clone(),
+                    locked_payment.token_nonce,
+                    reduce_result.penalty_amount,
+                ));
+                locked_payment.amount = nonce_unlocked_tokens.amount.clone();
+            }
 
-        self.set_energy_entry(&caller, reduce_result.energy);
-        self.unstake_tokens(caller, payment, unlocked_tokens);
+            if is_internal_unbond_mode {
+                let weighted_amount =
+                    &reduce_result.weighted_amount * &locked_payment.amount / &payment.amount;
+                self.add_unbond_entry(
+                    &caller,
+                    locked_payment,
+                    nonce_unlocked_tokens.amount.clone(),
+                    weighted_amount,
+                );
+            } else {
+                locked_tokens.push(locked_payment);
+            }
+            unlocked_tokens.merge_with(nonce_unlocked_tokens);
+        }
+
+        self.set_user_energy(&caller, energy);
+
+        if is_penalty_handled_locally {
+            self.distribute_penalty(&caller, &penalties);
+        }
+
+        // with the internal unbond mode, the base asset is only minted on claim
+        if !is_internal_unbond_mode {
+            self.send().esdt_local_mint(
+                &unlocked_tokens.token_identifier,
+                0,
+                &unlocked_tokens.amount,
+            );
+            self.unstake_tokens(caller, locked_tokens, unlocked_tokens);
+        }
     }
 
-    /// Reduce the locking period of a locked token. This incures a penalty.
+    /// Reduce the locking period of locked tokens. This incures a penalty.
     /// The longer the reduction, the bigger the penalty.
-    /// new_lock_period must be one of the available lock options
+    /// new_lock_period must be one of the available lock options.
+    /// The new unlock epoch is rounded like for lockTokens.
+    ///
+    /// Expected payments: any number of LOCKED tokens, with any nonces.
+    /// The penalty is computed separately for each of them.
+    ///
+    /// Output payments: a single LOCKED token position, with the new lock period
     #[payable("*")]
     #[endpoint(reduceLockPeriod)]
     fn reduce_lock_period(&self, new_lock_period: Epoch) -> EsdtTokenPayment {
         self.require_not_paused();
         self.require_is_listed_lock_option(new_lock_period);
 
-        let caller = self.blockchain().get_caller();
-        let payment = self.call_value().single_esdt();
-        let reduce_result =
-            self.reduce_lock_period_common(&caller, payment.clone(), Some(new_lock_period));
-
         let current_epoch = self.blockchain().get_block_epoch();
-        let new_unlock_epoch = current_epoch + reduce_result.new_lock_epochs;
+        let new_unlock_epoch = self.round_unlock_epoch(current_epoch + new_lock_period);
+
+        self.reduce_lock_period_batch(new_unlock_epoch, new_lock_period, None)
+    }
+
+    /// Same as reduceLockPeriod, but the new unlock epoch is given explicitly instead of rounded.
+    ///
+    /// Arguments:
+    /// - target_unlock_epoch - must be aligned to the unlock epoch granularity (a month by default),
+    ///     and before the current unlock epoch of all the payments.
+    ///     It must also be reachable through lockTokens, i.e. one of the lock options,
+    ///     added to the current epoch and rounded, gives this epoch (or the range, in range mode)
+    /// - max_penalty - the maximum total penalty the caller accepts, in LOCKED tokens
+    ///
+    /// Expected payments: any number of LOCKED tokens, with any nonces.
+    ///
+    /// Output payments: a single LOCKED token position, unlocking at `target_unlock_epoch`
+    #[payable("*")]
+    #[endpoint(reduceLockPeriodToEpoch)]
+    fn reduce_lock_period_to_epoch(
+        &self,
+        target_unlock_epoch: Epoch,
+        max_penalty: BigUint,
+    ) -> EsdtTokenPayment {
+        self.require_not_paused();
+        require!(
+            self.round_unlock_epoch(target_unlock_epoch) == target_unlock_epoch,
+            "Target unlock epoch not aligned to the unlock epoch granularity"
+        );
 
-        let unlocked_tokens = reduce_result.unlocked_tokens;
-        let penalty_amount = &payment.amount - &unlocked_tokens.amount;
-        let new_locked_tokens = self.lock_tokens(unlocked_tokens, new_unlock_epoch);
+        let new_lock_period = self.require_lock_period_for_unlock_epoch(target_unlock_epoch);
 
-        let amount_to_burn = &payment.amount - &penalty_amount;
-        self.send().esdt_local_burn(
-            &payment.token_identifier,
-            payment.token_nonce,
-            &amount_to_burn,
+        self.reduce_lock_period_batch(target_unlock_epoch, new_lock_period, Some(max_penalty))
+    }
+
+    fn reduce_lock_period_batch(
+        &self,
+        new_unlock_epoch: Epoch,
+        new_lock_period: Epoch,
+        opt_max_penalty: Option<BigUint>,
+    ) -> EsdtTokenPayment {
+        let caller = self.blockchain().get_caller();
+        let payments = self.get_non_empty_payments();
+        let current_epoch = self.blockchain().get_block_epoch();
+        require!(
+            new_unlock_epoch > current_epoch,
+            "Unlock epoch must be greater than the current epoch"
         );
-        if penalty_amount > 0 {
-            let fees = EsdtTokenPayment::new(
-                payment.token_identifier,
+
+        let base_asset = self.base_asset_token_id().get();
+        let mut unlocked_tokens = EsdtTokenPayment::new(base_asset, 0, BigUint::zero());
+        let mut penalties = ManagedVec::new();
+        let mut total_penalty = BigUint::zero();
+        let mut energy = self.get_updated_user_energy(&caller);
+        for payment in &payments {
+            let reduce_result =
+                self.reduce_lock_period_common(payment.clone(), new_unlock_epoch, &mut energy);
+            self.record_penalty(
+                &caller,
+                &reduce_result.penalty_amount,
+                reduce_result.prev_lock_epochs,
+                reduce_result.new_lock_epochs,
+            );
+
+            let amount_to_burn = &payment.amount - &reduce_result.penalty_amount;
+            self.send().esdt_local_burn(
+                &payment.token_identifier,
                 payment.token_nonce,
-                penalty_amount,
+                &amount_to_burn,
             );
-            self.send_fees_to_unstake_sc(fees);
+            if reduce_result.penalty_amount > 0 {
+                total_penalty += &reduce_result.penalty_amount;
+                penalties.push(EsdtTokenPayment::new(
+                    payment.token_identifier,
+                    payment.token_nonce,
+                    reduce_result.penalty_amount,
+                ));
+            }
+
+            unlocked_tokens.merge_with(self.to_esdt_payment(reduce_result.unlocked_tokens));
+        }
+
+        if let Some(max_penalty) = opt_max_penalty {
+            require!(total_penalty <= max_penalty, "Penalty above the maximum");
+        }
+
+        if self.is_penalty_handled_locally() {
+            self.distribute_penalty(&caller, &penalties);
+        } else {
+            for fees in &penalties {
+                self.send_fees_to_unstake_sc(fees);
+            }
         }
 
-        let mut energy = reduce_result.energy;
-        energy.add_after_token_lock(&new_locked_tokens.amount, new_unlock_epoch, current_epoch);
-        self.set_energy_entry(&caller, energy);
+        let new_locked_tokens = self.lock_tokens(
+            EgldOrEsdtTokenPayment::from(unlocked_tokens),
+            new_unlock_epoch,
+        );
+
+        self.add_energy_after_lock_reduce(
+            &mut energy,
+            &new_locked_tokens.amount,
+            new_lock_period,
+            new_unlock_epoch,
+        );
+        self.set_user_energy(&caller, energy);
 
         self.send().direct(
             &caller,
@@ -104,14 +265,84 @@ pub trait UnlockWithPenaltyModule:
         self.to_esdt_payment(new_locked_tokens)
     }
 
+    /// Quotes what unlockEarly would currently return for the given user.
+    /// The new unlock epoch is the current epoch, before any unbond period.
+    #[view(getUnlockEarlyQuote)]
+    fn get_unlock_early_quote(
+        &self,
+        user: ManagedAddress,
+        token_nonce: u64,
+        amount: BigUint,
+    ) -> LockReduceQuote<Self::Api> {
+        self.get_lock_reduce_quote(&user, token_nonce, amount, None)
+    }
+
+    /// Quotes what reduceLockPeriod would currently return for the given user
+    #[view(getReduceLockPeriodQuote)]
+    fn get_reduce_lock_period_quote(
+        &self,
+        user: ManagedAddress,
+        token_nonce: u64,
+        amount: BigUint,
+        new_lock_period: Epoch,
+    ) -> LockReduceQuote<Self::Api> {
+        self.require_is_listed_lock_option(new_lock_period);
+
+        self.get_lock_reduce_quote(&user, token_nonce, amount, Some(new_lock_period))
+    }
+
+    fn get_lock_reduce_quote(
+        &self,
+        user: &ManagedAddress,
+        token_nonce: u64,
+        amount: BigUint,
+        opt_new_lock_period: Option<Epoch>,
+    ) -> LockReduceQuote<Self::Api> {
+        let payment =
+            EsdtTokenPayment::new(self.locked_token().get_token_id(), token_nonce, amount);
+        let current_epoch = self.blockchain().get_block_epoch();
+        let new_unlock_epoch = match opt_new_lock_period {
+            Some(new_lock_period) => self.round_unlock_epoch(current_epoch + new_lock_period),
+            None => current_epoch,
+        };
+
+        let mut energy = self.get_updated_user_energy(user);
+        require!(
+            energy.get_total_locked_tokens() >= &payment.amount,
+            "Not enough locked tokens"
+        );
+
+        let prev_energy_amount = energy.get_energy_amount();
+        let reduce_result = self.reduce_lock_period_common(payment, new_unlock_epoch, &mut energy);
+
+        let received_amount = reduce_result.unlocked_tokens.amount;
+        if let Some(new_lock_period) = opt_new_lock_period {
+            self.add_energy_after_lock_reduce(
+                &mut energy,
+                &received_amount,
+                new_lock_period,
+                new_unlock_epoch,
+            );
+        }
+
+        LockReduceQuote {
+            penalty_amount: reduce_result.penalty_amount,
+            received_amount,
+            new_unlock_epoch,
+            energy_delta: BigInt::from(energy.get_energy_amount())
+                - BigInt::from(prev_energy_amount),
+        }
+    }
+
+    /// Does not write any storage or check the pause status, so it may also be used for quotes.
+    /// The energy lost by the reduction is depleted from the given entry.
+    /// For a full unlock, `new_unlock_epoch` should be the current epoch.
     fn reduce_lock_period_common(
         &self,
-        caller: &ManagedAddress,
         payment: EsdtTokenPayment,
-        opt_new_lock_period: Option<Epoch>,
+        new_unlock_epoch: Epoch,
+        energy: &mut UserEnergy<Self::Api>,
     ) -> LockReduceResult<Self::Api> {
-        self.require_not_paused();
-
         let locked_token_mapper = self.locked_token();
         locked_token_mapper.require_same_token(&payment.token_identifier);
 
@@ -124,41 +355,57 @@ pub trait UnlockWithPenaltyModule:
             TOKEN_CAN_BE_UNLOCKED_ALREADY_ERR_MSG
         );
 
-        let new_lock_epochs = match opt_new_lock_period {
-            Some(lock_epochs) => {
-                let tentative_new_unlock_epoch = current_epoch + lock_epochs;
-                let start_of_month_epoch =
-                    self.unlock_epoch_to_start_of_month(tentative_new_unlock_epoch);
-                let epochs_diff_from_month_start =
-                    tentative_new_unlock_epoch - start_of_month_epoch;
-
-                lock_epochs - epochs_diff_from_month_start
-            }
-            None => 0,
-        };
+        require!(new_unlock_epoch >= current_epoch, "Invalid reduce choice");
 
         let prev_lock_epochs = attributes.unlock_epoch - current_epoch;
+        let new_lock_epochs = new_unlock_epoch - current_epoch;
         require!(new_lock_epochs < prev_lock_epochs, "Invalid reduce choice");
 
-        let mut energy = self.get_updated_energy_entry_for_user(caller);
-        energy.deplete_after_early_unlock(&payment.amount, attributes.unlock_epoch, current_epoch);
-
-        let penalty_amount =
-            self.calculate_penalty_amount(&payment.amount, prev_lock_epochs, new_lock_epochs);
+        let (penalty_amount, weighted_amount) = self.calculate_discounted_penalty_amount(
+            energy,
+            &payment.amount,
+            attributes.unlock_epoch,
+            new_lock_epochs,
+        );
         let mut unlocked_tokens = self.unlock_tokens_unchecked(payment, &attributes);
         require!(
             unlocked_tokens.amount > penalty_amount,
             "No tokens remaining after penalty is applied"
         );
-        unlocked_tokens.amount -= penalty_amount;
+        unlocked_tokens.amount -= &penalty_amount;
 
         LockReduceResult {
+            prev_lock_epochs,
             new_lock_epochs,
-            energy,
+            penalty_amount,
             unlocked_tokens,
+            weighted_amount,
         }
     }
 
+    /// Without a penalty distribution, penalties are handled by the unstake SC,
+    /// unless the internal unbond mode is set, in which case they are all burned
+    fn is_penalty_handled_locally(&self) -> bool {
+        self.is_penalty_distribution_set() || self.is_internal_unbond_mode()
+    }
+
+    fn add_energy_after_lock_reduce(
+        &self,
+        energy: &mut UserEnergy<Self::Api>,
+        new_locked_amount: &BigUint,
+        new_lock_period: Epoch,
+        new_unlock_epoch: Epoch,
+    ) {
+        let weighted_amount = self.get_weighted_lock_amount(new_locked_amount, new_lock_period);
+        energy.add_after_weighted_token_lock(
+            new_locked_amount,
+            &weighted_amount,
+            new_unlock_epoch,
+            self.get_energy_epoch(),
+        );
+    }
+
+    /// The result is scaled by PENALTY_PRECISION
     fn calculate_penalty_percentage_partial_unlock(
         &self,
         prev_lock_epochs_remaining: Epoch,
@@ -168,20 +415,83 @@ pub trait UnlockWithPenaltyModule:
             self.calculate_penalty_percentage_full_unlock(prev_lock_epochs_remaining);
         let new_penalty_percentage =
             self.calculate_penalty_percentage_full_unlock(new_lock_epochs_remaining);
+        if prev_penalty_percentage_full <= new_penalty_percentage {
+            return 0;
+        }
 
-        (prev_penalty_percentage_full - new_penalty_percentage) * MAX_PENALTY_PERCENTAGE
-            / (MAX_PENALTY_PERCENTAGE - new_penalty_percentage)
+        let penalty_percentage_diff =
+            (prev_penalty_percentage_full - new_penalty_percentage) as u128;
+        let remaining_percentage = (PENALTY_PRECISION - new_penalty_percentage) as u128;
+
+        (penalty_percentage_diff * PENALTY_PRECISION as u128 / remaining_percentage) as u64
     }
 
     /// Calculates the penalty that would be incurred if `token_amount` tokens
     /// were to have their lock epochs reduced from `prev_lock_epochs` to
     /// `new_lock_epochs`. For full unlock, `new_lock_epochs` should be 0.
+    /// If `opt_user` is provided, the user's penalty discount is applied,
+    /// as for unlockEarly and reduceLockPeriod with tokens unlocking after `prev_lock_epochs`.
     #[view(getPenaltyAmount)]
     fn calculate_penalty_amount(
         &self,
         token_amount: &BigUint,
         prev_lock_epochs: Epoch,
         new_lock_epochs: Epoch,
+        opt_user: OptionalValue<ManagedAddress>,
+    ) -> BigUint {
+        match opt_user {
+            OptionalValue::Some(user) => {
+                require!(prev_lock_epochs > 0, TOKEN_CAN_BE_UNLOCKED_ALREADY_ERR_MSG);
+
+                let mut energy = self.get_updated_user_energy(&user);
+                require!(
+                    energy.get_total_locked_tokens() >= token_amount,
+                    "Not enough locked tokens"
+                );
+
+                let unlock_epoch = self.blockchain().get_block_epoch() + prev_lock_epochs;
+                let (penalty_amount, _) = self.calculate_discounted_penalty_amount(
+                    &mut energy,
+                    token_amount,
+                    unlock_epoch,
+                    new_lock_epochs,
+                );
+
+                penalty_amount
+            }
+            OptionalValue::None => {
+                self.calculate_base_penalty_amount(token_amount, prev_lock_epochs, new_lock_epochs)
+            }
+        }
+    }
+
+    /// The discount is based on the energy left after unlocking the tokens,
+    /// which is depleted from the given entry.
+    /// Also returns the weighted amount of the tokens, as removed from the entry.
+    fn calculate_discounted_penalty_amount(
+        &self,
+        energy: &mut UserEnergy<Self::Api>,
+        token_amount: &BigUint,
+        unlock_epoch: Epoch,
+        new_lock_epochs: Epoch,
+    ) -> (BigUint, BigUint) {
+        let prev_lock_epochs = unlock_epoch - self.blockchain().get_block_epoch();
+        let weighted_amount =
+            energy.deplete_after_early_unlock(token_amount, unlock_epoch, self.get_energy_epoch());
+
+        let penalty_amount =
+            self.calculate_base_penalty_amount(token_amount, prev_lock_epochs, new_lock_epochs);
+        let penalty_amount =
+            self.apply_penalty_discount(penalty_amount, &energy.get_energy_amount());
+
+        (penalty_amount, weighted_amount)
+    }
+
+    fn calculate_base_penalty_amount(
+        &self,
+        token_amount: &BigUint,
+        prev_lock_epochs: Epoch,
+        new_lock_epochs: Epoch,
     ) -> BigUint {
         require!(prev_lock_epochs > 0, TOKEN_CAN_BE_UNLOCKED_ALREADY_ERR_MSG);
         require!(new_lock_epochs < prev_lock_epochs, "Invalid new lock epoch");
@@ -192,6 +502,6 @@ pub trait UnlockWithPenaltyModule:
             self.calculate_penalty_percentage_partial_unlock(prev_lock_epochs, new_lock_epochs)
         };
 
-        token_amount * penalty_percentage_unlock / MAX_PENALTY_PERCENTAGE
+        token_amount * penalty_percentage_unlock / PENALTY_PRECISION
     }
 }
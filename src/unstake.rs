@@ -27,6 +27,9 @@ pub trait UnstakeModule:
     + multiversx_sc_modules::pause::PauseModule
     + crate::penalty::LocalPenaltyModule
     + crate::energy::EnergyModule
+    + crate::energy_checkpoint::EnergyCheckpointModule
+    + crate::energy_decay::EnergyDecayModule
+    + crate::energy_subscribers::EnergySubscribersModule
     + crate::events::EventsModule
     + crate::lock_options::LockOptionsModule
     + utils::UtilsModule
@@ -51,15 +54,18 @@ pub trait UnstakeModule:
         self.set_energy_entry(&user, new_energy);
     }
 
+    /// Sends all the locked tokens, followed by the total unlocked tokens, in a single deposit.
+    /// With a single locked payment, these are the two payments the unstake SC always expected,
+    /// while for several of them, its `depositUserTokens` must accept any number of locked payments,
+    /// unbonding the unlocked tokens against all of them
     fn unstake_tokens(
         &self,
         caller: ManagedAddress,
-        locked_tokens: EsdtTokenPayment,
+        locked_tokens: ManagedVec<EsdtTokenPayment>,
         unlocked_tokens: EsdtTokenPayment,
     ) {
         let locking_sc_address = self.token_unstake_sc_address().get();
-        let mut payments = ManagedVec::new();
-        payments.push(locked_tokens);
+        let mut payments = locked_tokens;
         payments.push(unlocked_tokens);
 
         let _: IgnoreValue = self
@@ -1,6 +1,6 @@
 multiversx_sc::imports!();
 
-use crate::energy::Energy;
+use crate::energy::UserEnergy;
 use common_structs::Epoch;
 
 #[multiversx_sc::module]
@@ -11,8 +11,12 @@ pub trait VirtualLockModule:
     + multiversx_sc_modules::default_issue_callbacks::DefaultIssueCallbacksModule
     + crate::token_whitelist::TokenWhitelistModule
     + crate::energy::EnergyModule
+    + crate::energy_checkpoint::EnergyCheckpointModule
+    + crate::energy_decay::EnergyDecayModule
+    + crate::energy_subscribers::EnergySubscribersModule
     + crate::lock_options::LockOptionsModule
     + crate::events::EventsModule
+    + crate::lock_cancellation::LockCancellationModule
     + crate::migration::SimpleLockMigrationModule
     + multiversx_sc_modules::pause::PauseModule
     + utils::UtilsModule
@@ -41,7 +45,7 @@ pub trait VirtualLockModule:
         self.require_sc_address_whitelisted(&caller);
 
         let current_epoch = self.blockchain().get_block_epoch();
-        let unlock_epoch = self.unlock_epoch_to_start_of_month(current_epoch + lock_epochs);
+        let unlock_epoch = self.round_unlock_epoch(current_epoch + lock_epochs);
 
         require!(
             unlock_epoch > current_epoch,
@@ -49,14 +53,15 @@ pub trait VirtualLockModule:
         );
 
         let locked_tokens =
-            self.update_energy(&energy_address, |energy: &mut Energy<Self::Api>| {
+            self.update_energy(&energy_address, |energy: &mut UserEnergy<Self::Api>| {
                 self.lock_base_asset(
                     EsdtTokenPayment::new(token_id, 0, amount),
+                    lock_epochs,
                     unlock_epoch,
-                    current_epoch,
                     energy,
                 )
             });
+
         self.send().direct_esdt(
             &dest_address,
             &locked_tokens.token_identifier,
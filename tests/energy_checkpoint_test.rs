@@ -0,0 +1,131 @@
+#![allow(deprecated)]
+
+mod energy_factory_setup;
+
+use energy_factory::energy::{Energy, EnergyModule};
+use energy_factory_setup::*;
+use multiversx_sc::types::BigInt;
+
+use multiversx_sc_scenario::{managed_address, managed_biguint, rust_biguint};
+
+#[test]
+fn energy_at_epoch_test() {
+    let mut setup = SimpleLockEnergySetup::new(energy_factory::contract_obj);
+    let first_user = setup.first_user.clone();
+    let half_balance = USER_BALANCE / 2;
+
+    let first_lock_epoch = 10;
+    setup.b_mock.set_block_epoch(first_lock_epoch);
+    setup
+        .lock(
+            &first_user,
+            BASE_ASSET_TOKEN_ID,
+            half_balance,
+            LOCK_OPTIONS[0],
+        )
+        .assert_ok();
+
+    let second_lock_epoch = 50;
+    setup.b_mock.set_block_epoch(second_lock_epoch);
+    setup
+        .lock(
+            &first_user,
+            BASE_ASSET_TOKEN_ID,
+            half_balance,
+            LOCK_OPTIONS[0],
+        )
+        .assert_ok();
+
+    let current_epoch = 100;
+    setup.b_mock.set_block_epoch(current_epoch);
+
+    let first_unlock_epoch = to_start_of_month(first_lock_epoch + LOCK_OPTIONS[0]);
+    let second_unlock_epoch = to_start_of_month(second_lock_epoch + LOCK_OPTIONS[0]);
+
+    // before the first lock
+    assert_eq!(
+        setup.get_user_energy_at_epoch(&first_user, first_lock_epoch - 1),
+        rust_biguint!(0)
+    );
+
+    // between the two locks
+    let epoch = 30;
+    assert_eq!(
+        setup.get_user_energy_at_epoch(&first_user, epoch),
+        rust_biguint!(half_balance) * (first_unlock_epoch - epoch)
+    );
+
+    // after the second lock
+    let epoch = 75;
+    assert_eq!(
+        setup.get_user_energy_at_epoch(&first_user, epoch),
+        rust_biguint!(half_balance) * (first_unlock_epoch - epoch)
+            + rust_biguint!(half_balance) * (second_unlock_epoch - epoch)
+    );
+
+    // current epoch matches the live energy
+    assert_eq!(
+        setup.get_user_energy_at_epoch(&first_user, current_epoch),
+        setup.get_user_energy(&first_user)
+    );
+
+    // future epochs are rejected
+    setup
+        .b_mock
+        .execute_query(&setup.sc_wrapper, |sc| {
+            let _ = sc.get_energy_at_epoch(managed_address!(&first_user), current_epoch + 1);
+        })
+        .assert_user_error("Epoch cannot be in the future");
+}
+
+#[test]
+fn energy_at_epoch_without_checkpoint_test() {
+    let mut setup = SimpleLockEnergySetup::new(energy_factory::contract_obj);
+    let first_user = setup.first_user.clone();
+    let locked_amount = 1_000u64;
+    let entry_epoch = 10;
+    let entry_energy = locked_amount * 100;
+
+    // entries saved before checkpoints existed
+    setup
+        .b_mock
+        .execute_tx(&setup.owner, &setup.sc_wrapper, &rust_biguint!(0), |sc| {
+            let energy_entry = Energy::new(
+                BigInt::from(managed_biguint!(entry_energy)),
+                entry_epoch,
+                managed_biguint!(locked_amount),
+            );
+            sc.user_energy(&managed_address!(&first_user))
+                .set(&energy_entry);
+        })
+        .assert_ok();
+
+    setup.b_mock.set_block_epoch(40);
+
+    // before the entry was saved
+    assert_eq!(
+        setup.get_user_energy_at_epoch(&first_user, entry_epoch - 1),
+        rust_biguint!(0)
+    );
+
+    let epoch = 30;
+    let expected_energy = rust_biguint!(entry_energy - locked_amount * (epoch - entry_epoch));
+    assert_eq!(
+        setup.get_user_energy_at_epoch(&first_user, epoch),
+        expected_energy
+    );
+
+    // the entry is kept as the first checkpoint on the next update
+    setup
+        .lock(
+            &first_user,
+            BASE_ASSET_TOKEN_ID,
+            USER_BALANCE,
+            LOCK_OPTIONS[0],
+        )
+        .assert_ok();
+    assert_eq!(
+        setup.get_user_energy_at_epoch(&first_user, epoch),
+        expected_energy
+    );
+}
@@ -0,0 +1,100 @@
+#![allow(deprecated)]
+
+mod energy_factory_setup;
+
+use energy_factory::energy_decay::{EnergyDecayMode, EnergyDecayModule};
+use energy_factory_setup::*;
+
+use multiversx_sc_scenario::rust_biguint;
+
+#[test]
+fn weekly_energy_decay_test() {
+    let mut setup = SimpleLockEnergySetup::new(energy_factory::contract_obj);
+    let first_user = setup.first_user.clone();
+    let second_user = setup.second_user.clone();
+    let half_balance = USER_BALANCE / 2;
+
+    let mut current_epoch = 10;
+    setup.b_mock.set_block_epoch(current_epoch);
+
+    setup
+        .lock(
+            &first_user,
+            BASE_ASSET_TOKEN_ID,
+            half_balance,
+            LOCK_OPTIONS[0],
+        )
+        .assert_ok();
+    let unlock_epoch = to_start_of_month(current_epoch + LOCK_OPTIONS[0]);
+
+    setup
+        .b_mock
+        .execute_tx(&setup.owner, &setup.sc_wrapper, &rust_biguint!(0), |sc| {
+            sc.set_energy_decay_mode(EnergyDecayMode::Weekly);
+        })
+        .assert_ok();
+    setup
+        .b_mock
+        .execute_tx(&setup.owner, &setup.sc_wrapper, &rust_biguint!(0), |sc| {
+            sc.set_energy_decay_mode(EnergyDecayMode::Weekly);
+        })
+        .assert_user_error("Decay mode already set");
+
+    // the first week only starts when the mode is set
+    current_epoch = 13;
+    setup.b_mock.set_block_epoch(current_epoch);
+    assert_eq!(
+        setup.get_user_energy(&first_user),
+        rust_biguint!(half_balance) * (unlock_epoch - 10)
+    );
+
+    // energy decays in steps at week boundaries
+    current_epoch = 14;
+    setup.b_mock.set_block_epoch(current_epoch);
+    assert_eq!(
+        setup.get_user_energy(&first_user),
+        rust_biguint!(half_balance) * (unlock_epoch - 14)
+    );
+
+    current_epoch = 20;
+    setup.b_mock.set_block_epoch(current_epoch);
+    assert_eq!(
+        setup.get_user_energy(&first_user),
+        rust_biguint!(half_balance) * (unlock_epoch - 14)
+    );
+
+    // tokens locked mid-week are accounted from the start of the week
+    setup
+        .lock(
+            &second_user,
+            BASE_ASSET_TOKEN_ID,
+            half_balance,
+            LOCK_OPTIONS[0],
+        )
+        .assert_ok();
+    let second_unlock_epoch = to_start_of_month(current_epoch + LOCK_OPTIONS[0]);
+    assert_eq!(
+        setup.get_user_energy(&second_user),
+        rust_biguint!(half_balance) * (second_unlock_epoch - 14)
+    );
+
+    // early unlock removes the remaining energy as of the start of the week
+    setup.unlock_early(&first_user, 1, half_balance).assert_ok();
+    assert_eq!(setup.get_user_energy(&first_user), rust_biguint!(0));
+    assert_eq!(
+        setup.get_total_energy(),
+        rust_biguint!(half_balance) * (second_unlock_epoch - 14)
+    );
+
+    // back to continuous decay
+    setup
+        .b_mock
+        .execute_tx(&setup.owner, &setup.sc_wrapper, &rust_biguint!(0), |sc| {
+            sc.set_energy_decay_mode(EnergyDecayMode::Continuous);
+        })
+        .assert_ok();
+    assert_eq!(
+        setup.get_user_energy(&second_user),
+        rust_biguint!(half_balance) * (second_unlock_epoch - current_epoch)
+    );
+}
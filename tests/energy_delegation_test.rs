@@ -0,0 +1,86 @@
+#![allow(deprecated)]
+
+mod energy_factory_setup;
+
+use energy_factory_setup::*;
+
+use multiversx_sc_scenario::rust_biguint;
+
+#[test]
+fn energy_delegation_test() {
+    let mut setup = SimpleLockEnergySetup::new(energy_factory::contract_obj);
+    let first_user = setup.first_user.clone();
+    let second_user = setup.second_user.clone();
+    let half_balance = USER_BALANCE / 2;
+
+    let mut current_epoch = 1;
+    setup.b_mock.set_block_epoch(current_epoch);
+
+    setup
+        .lock(
+            &first_user,
+            BASE_ASSET_TOKEN_ID,
+            half_balance,
+            LOCK_OPTIONS[0],
+        )
+        .assert_ok();
+    setup
+        .lock(
+            &second_user,
+            BASE_ASSET_TOKEN_ID,
+            half_balance,
+            LOCK_OPTIONS[1],
+        )
+        .assert_ok();
+
+    setup
+        .delegate_energy(&first_user, &first_user)
+        .assert_user_error("Cannot delegate energy to self");
+    setup.delegate_energy(&first_user, &second_user).assert_ok();
+    setup
+        .delegate_energy(&first_user, &second_user)
+        .assert_user_error("Energy already delegated to this address");
+
+    let first_unlock_epoch = to_start_of_month(current_epoch + LOCK_OPTIONS[0]);
+    let second_unlock_epoch = to_start_of_month(current_epoch + LOCK_OPTIONS[1]);
+    let first_user_energy = rust_biguint!(half_balance) * (first_unlock_epoch - current_epoch);
+    let second_user_energy = rust_biguint!(half_balance) * (second_unlock_epoch - current_epoch);
+    assert_eq!(setup.get_effective_energy(&first_user), rust_biguint!(0));
+    assert_eq!(
+        setup.get_effective_energy(&second_user),
+        &first_user_energy + &second_user_energy
+    );
+    assert_eq!(setup.get_user_energy(&first_user), first_user_energy);
+
+    // delegated energy decays and follows the delegator's updates
+    current_epoch += 100;
+    setup.b_mock.set_block_epoch(current_epoch);
+
+    setup
+        .lock(
+            &first_user,
+            BASE_ASSET_TOKEN_ID,
+            half_balance,
+            LOCK_OPTIONS[0],
+        )
+        .assert_ok();
+
+    let third_unlock_epoch = to_start_of_month(current_epoch + LOCK_OPTIONS[0]);
+    let first_user_energy = rust_biguint!(half_balance) * (first_unlock_epoch - current_epoch)
+        + rust_biguint!(half_balance) * (third_unlock_epoch - current_epoch);
+    let second_user_energy = rust_biguint!(half_balance) * (second_unlock_epoch - current_epoch);
+    assert_eq!(setup.get_user_energy(&first_user), first_user_energy);
+    assert_eq!(setup.get_effective_energy(&first_user), rust_biguint!(0));
+    assert_eq!(
+        setup.get_effective_energy(&second_user),
+        &first_user_energy + &second_user_energy
+    );
+
+    setup.undelegate_energy(&first_user).assert_ok();
+    setup
+        .undelegate_energy(&first_user)
+        .assert_user_error("Energy not delegated");
+
+    assert_eq!(setup.get_effective_energy(&first_user), first_user_energy);
+    assert_eq!(setup.get_effective_energy(&second_user), second_user_energy);
+}
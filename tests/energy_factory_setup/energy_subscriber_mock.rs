@@ -0,0 +1,61 @@
+use energy_factory::energy::Energy;
+use multiversx_sc::io::load_endpoint_args;
+use multiversx_sc::{
+    contract_base::{CallableContract, ContractBase},
+    storage::{
+        mappers::{SingleValueMapper, StorageMapper},
+        StorageKey,
+    },
+    types::{BigUint, ManagedAddress},
+};
+use multiversx_sc_scenario::DebugApi;
+
+static ON_ENERGY_UPDATED_FN_NAME: &str = "onEnergyUpdated";
+
+#[derive(Clone)]
+pub struct EnergySubscriberMock {}
+
+impl ContractBase for EnergySubscriberMock {
+    type Api = DebugApi;
+}
+
+impl CallableContract for EnergySubscriberMock {
+    fn call(&self, fn_name: &str) -> bool {
+        if fn_name == ON_ENERGY_UPDATED_FN_NAME {
+            self.on_energy_updated();
+            true
+        } else {
+            false
+        }
+    }
+}
+
+impl EnergySubscriberMock {
+    pub fn new() -> Self {
+        EnergySubscriberMock {}
+    }
+
+    // Only keeps track of the number of notifications and the last reported energy
+    pub fn on_energy_updated(&self) {
+        let (_user, (_old_energy, (new_energy, ()))) =
+            load_endpoint_args::<
+                DebugApi,
+                (
+                    ManagedAddress<DebugApi>,
+                    (Energy<DebugApi>, (Energy<DebugApi>, ())),
+                ),
+            >(("user", ("old_energy", ("new_energy", ()))));
+
+        self.notifications_count().update(|count| *count += 1);
+        self.last_energy_amount()
+            .set(new_energy.get_energy_amount());
+    }
+
+    pub fn notifications_count(&self) -> SingleValueMapper<DebugApi, usize> {
+        SingleValueMapper::new(StorageKey::new(b"notificationsCount"))
+    }
+
+    pub fn last_energy_amount(&self) -> SingleValueMapper<DebugApi, BigUint<DebugApi>> {
+        SingleValueMapper::new(StorageKey::new(b"lastEnergyAmount"))
+    }
+}
@@ -1,16 +1,19 @@
 #![allow(dead_code)]
 #![allow(deprecated)]
 
+pub mod energy_subscriber_mock;
+pub mod fees_collector_mock;
 pub mod unbond_sc_mock;
 
 use energy_factory::{
-    energy::EnergyModule, unlock_with_penalty::UnlockWithPenaltyModule, unstake::UnstakeModule,
-    SimpleLockEnergy,
+    energy::EnergyModule, energy_delegation::EnergyDelegationModule,
+    lock_cancellation::LockCancellationModule, unlock_with_penalty::UnlockWithPenaltyModule,
+    unstake::UnstakeModule, SimpleLockEnergy,
 };
 use multiversx_sc::{
     codec::multi_types::OptionalValue,
     storage::mappers::StorageTokenWrapper,
-    types::{Address, EsdtLocalRole, MultiValueEncoded},
+    types::{Address, BigUint, EsdtLocalRole, MultiValueEncoded},
 };
 use multiversx_sc_modules::pause::PauseModule;
 use multiversx_sc_scenario::{
@@ -221,6 +224,33 @@ where
         )
     }
 
+    pub fn cancel_lock(&mut self, caller: &Address, token_nonce: u64, amount: u64) -> TxResult {
+        self.b_mock.execute_esdt_transfer(
+            caller,
+            &self.sc_wrapper,
+            LOCKED_TOKEN_ID,
+            token_nonce,
+            &rust_biguint!(amount),
+            |sc| {
+                sc.cancel_lock();
+            },
+        )
+    }
+
+    pub fn delegate_energy(&mut self, caller: &Address, delegatee: &Address) -> TxResult {
+        self.b_mock
+            .execute_tx(caller, &self.sc_wrapper, &rust_biguint!(0), |sc| {
+                sc.delegate_energy(managed_address!(delegatee));
+            })
+    }
+
+    pub fn undelegate_energy(&mut self, caller: &Address) -> TxResult {
+        self.b_mock
+            .execute_tx(caller, &self.sc_wrapper, &rust_biguint!(0), |sc| {
+                sc.undelegate_energy();
+            })
+    }
+
     pub fn reduce_lock_period(
         &mut self,
         caller: &Address,
@@ -240,6 +270,29 @@ where
         )
     }
 
+    pub fn reduce_lock_period_to_epoch(
+        &mut self,
+        caller: &Address,
+        token_nonce: u64,
+        amount: u64,
+        target_unlock_epoch: u64,
+        max_penalty: &num_bigint::BigUint,
+    ) -> TxResult {
+        self.b_mock.execute_esdt_transfer(
+            caller,
+            &self.sc_wrapper,
+            LOCKED_TOKEN_ID,
+            token_nonce,
+            &rust_biguint!(amount),
+            |sc| {
+                sc.reduce_lock_period_to_epoch(
+                    target_unlock_epoch,
+                    BigUint::from_bytes_be(&max_penalty.to_bytes_be()),
+                );
+            },
+        )
+    }
+
     pub fn get_penalty_amount(
         &mut self,
         token_amount: u64,
@@ -253,6 +306,7 @@ where
                     &managed_biguint!(token_amount),
                     prev_lock_epochs,
                     new_lock_epochs,
+                    OptionalValue::None,
                 );
                 result = to_rust_biguint(managed_result);
             })
@@ -272,6 +326,54 @@ where
 
         result
     }
+
+    pub fn get_total_energy(&mut self) -> num_bigint::BigUint {
+        let mut result = rust_biguint!(0);
+        self.b_mock
+            .execute_query(&self.sc_wrapper, |sc| {
+                let managed_result = sc.get_total_energy();
+                result = to_rust_biguint(managed_result);
+            })
+            .assert_ok();
+
+        result
+    }
+
+    pub fn get_total_locked_tokens(&mut self) -> num_bigint::BigUint {
+        let mut result = rust_biguint!(0);
+        self.b_mock
+            .execute_query(&self.sc_wrapper, |sc| {
+                let managed_result = sc.get_total_locked_tokens();
+                result = to_rust_biguint(managed_result);
+            })
+            .assert_ok();
+
+        result
+    }
+
+    pub fn get_user_energy_at_epoch(&mut self, user: &Address, epoch: u64) -> num_bigint::BigUint {
+        let mut result = rust_biguint!(0);
+        self.b_mock
+            .execute_query(&self.sc_wrapper, |sc| {
+                let managed_result = sc.get_energy_at_epoch(managed_address!(user), epoch);
+                result = to_rust_biguint(managed_result);
+            })
+            .assert_ok();
+
+        result
+    }
+
+    pub fn get_effective_energy(&mut self, user: &Address) -> num_bigint::BigUint {
+        let mut result = rust_biguint!(0);
+        self.b_mock
+            .execute_query(&self.sc_wrapper, |sc| {
+                let managed_result = sc.get_effective_energy_for_user(managed_address!(user));
+                result = to_rust_biguint(managed_result);
+            })
+            .assert_ok();
+
+        result
+    }
 }
 
 pub fn to_rust_biguint(
@@ -32,15 +32,26 @@ impl UnbondScMock {
     }
 
     // We don't test cancel unbond here, so we simply send to the user
+    // Payments are the locked tokens, followed by the total unlocked tokens
     pub fn send_to_user(&self) {
-        let [locked_tokens, unlocked_tokens] = self.call_value().multi_esdt();
+        let mut payments = self.call_value().all_esdt_transfers().clone_value();
+        let unlocked_tokens = payments.get(payments.len() - 1);
+        payments.remove(payments.len() - 1);
 
-        let locked_tokens_burn_amount = &unlocked_tokens.amount;
-        self.send().esdt_local_burn(
-            &locked_tokens.token_identifier,
-            locked_tokens.token_nonce,
-            locked_tokens_burn_amount,
-        );
+        let mut remaining_burn_amount = unlocked_tokens.amount.clone();
+        for locked_tokens in &payments {
+            let locked_tokens_burn_amount = if locked_tokens.amount < remaining_burn_amount {
+                locked_tokens.amount.clone()
+            } else {
+                remaining_burn_amount.clone()
+            };
+            self.send().esdt_local_burn(
+                &locked_tokens.token_identifier,
+                locked_tokens.token_nonce,
+                &locked_tokens_burn_amount,
+            );
+            remaining_burn_amount -= locked_tokens_burn_amount;
+        }
 
         let (dest_user, ()) =
             load_endpoint_args::<DebugApi, (ManagedAddress<DebugApi>, ())>(("dest_user", ()));
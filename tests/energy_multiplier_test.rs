@@ -0,0 +1,96 @@
+#![allow(deprecated)]
+
+mod energy_factory_setup;
+
+use energy_factory::lock_options_endpoints::LockOptionsEndpointsModule;
+use energy_factory_setup::*;
+
+use multiversx_sc_scenario::rust_biguint;
+
+#[test]
+fn energy_multiplier_test() {
+    let mut setup = SimpleLockEnergySetup::new(energy_factory::contract_obj);
+    let first_user = setup.first_user.clone();
+    let second_user = setup.second_user.clone();
+    let half_balance = USER_BALANCE / 2;
+
+    setup
+        .b_mock
+        .execute_tx(&setup.owner, &setup.sc_wrapper, &rust_biguint!(0), |sc| {
+            sc.set_lock_option_energy_multiplier(LOCK_OPTIONS[2] + 1, 12_000);
+        })
+        .assert_user_error("Invalid lock choice");
+    setup
+        .b_mock
+        .execute_tx(&setup.owner, &setup.sc_wrapper, &rust_biguint!(0), |sc| {
+            sc.set_lock_option_energy_multiplier(LOCK_OPTIONS[2], 0);
+        })
+        .assert_user_error("Invalid energy multiplier");
+    setup
+        .b_mock
+        .execute_tx(&setup.owner, &setup.sc_wrapper, &rust_biguint!(0), |sc| {
+            sc.set_lock_option_energy_multiplier(LOCK_OPTIONS[2], 12_000);
+        })
+        .assert_ok();
+
+    let mut current_epoch = 1;
+    setup.b_mock.set_block_epoch(current_epoch);
+
+    setup
+        .lock(
+            &first_user,
+            BASE_ASSET_TOKEN_ID,
+            half_balance,
+            LOCK_OPTIONS[2],
+        )
+        .assert_ok();
+    setup
+        .lock(
+            &second_user,
+            BASE_ASSET_TOKEN_ID,
+            half_balance,
+            LOCK_OPTIONS[0],
+        )
+        .assert_ok();
+
+    let first_unlock_epoch = to_start_of_month(current_epoch + LOCK_OPTIONS[2]);
+    let second_unlock_epoch = to_start_of_month(current_epoch + LOCK_OPTIONS[0]);
+    let weighted_amount = rust_biguint!(half_balance) * 12_000u64 / 10_000u64;
+    assert_eq!(
+        setup.get_user_energy(&first_user),
+        &weighted_amount * (first_unlock_epoch - current_epoch)
+    );
+    assert_eq!(
+        setup.get_user_energy(&second_user),
+        rust_biguint!(half_balance) * (second_unlock_epoch - current_epoch)
+    );
+
+    // existing positions keep their original weight
+    setup
+        .b_mock
+        .execute_tx(&setup.owner, &setup.sc_wrapper, &rust_biguint!(0), |sc| {
+            sc.set_lock_option_energy_multiplier(LOCK_OPTIONS[2], 15_000);
+        })
+        .assert_ok();
+
+    current_epoch += 100;
+    setup.b_mock.set_block_epoch(current_epoch);
+
+    assert_eq!(
+        setup.get_user_energy(&first_user),
+        &weighted_amount * (first_unlock_epoch - current_epoch)
+    );
+    assert_eq!(
+        setup.get_total_energy(),
+        &weighted_amount * (first_unlock_epoch - current_epoch)
+            + rust_biguint!(half_balance) * (second_unlock_epoch - current_epoch)
+    );
+
+    // unlocking removes the whole weighted energy
+    setup.unlock_early(&first_user, 1, half_balance).assert_ok();
+    assert_eq!(setup.get_user_energy(&first_user), rust_biguint!(0));
+    assert_eq!(
+        setup.get_total_energy(),
+        rust_biguint!(half_balance) * (second_unlock_epoch - current_epoch)
+    );
+}
@@ -0,0 +1,219 @@
+#![allow(deprecated)]
+
+mod energy_factory_setup;
+
+use energy_factory::{
+    energy::{Energy, EnergyModule},
+    energy_reconciliation::EnergyReconciliationModule,
+    lock_options_endpoints::LockOptionsEndpointsModule,
+};
+use energy_factory_setup::*;
+use simple_lock::locked_token::LockedTokenAttributes;
+
+use multiversx_sc_scenario::{
+    managed_address, managed_token_id_wrapped, rust_biguint, whitebox_legacy::TxTokenTransfer,
+    DebugApi,
+};
+
+#[test]
+fn recompute_energy_test() {
+    let mut setup = SimpleLockEnergySetup::new(energy_factory::contract_obj);
+    let first_user = setup.first_user.clone();
+    let half_balance = USER_BALANCE / 2;
+
+    let mut current_epoch = 1;
+    setup.b_mock.set_block_epoch(current_epoch);
+
+    setup
+        .lock(
+            &first_user,
+            BASE_ASSET_TOKEN_ID,
+            half_balance,
+            LOCK_OPTIONS[0],
+        )
+        .assert_ok();
+    setup
+        .lock(
+            &first_user,
+            BASE_ASSET_TOKEN_ID,
+            half_balance,
+            LOCK_OPTIONS[1],
+        )
+        .assert_ok();
+    let first_unlock_epoch = to_start_of_month(current_epoch + LOCK_OPTIONS[0]);
+    let second_unlock_epoch = to_start_of_month(current_epoch + LOCK_OPTIONS[1]);
+
+    // simulate a wrong energy entry
+    setup
+        .b_mock
+        .execute_tx(&setup.owner, &setup.sc_wrapper, &rust_biguint!(0), |sc| {
+            sc.set_energy_entry(
+                &managed_address!(&first_user),
+                Energy::new_zero_energy(current_epoch),
+            );
+        })
+        .assert_ok();
+    assert_eq!(setup.get_user_energy(&first_user), rust_biguint!(0));
+
+    current_epoch = 10;
+    setup.b_mock.set_block_epoch(current_epoch);
+
+    let payments = [
+        TxTokenTransfer {
+            token_identifier: LOCKED_TOKEN_ID.to_vec(),
+            nonce: 1,
+            value: rust_biguint!(half_balance),
+        },
+        TxTokenTransfer {
+            token_identifier: LOCKED_TOKEN_ID.to_vec(),
+            nonce: 2,
+            value: rust_biguint!(half_balance),
+        },
+    ];
+    setup
+        .b_mock
+        .execute_esdt_multi_transfer(&first_user, &setup.sc_wrapper, &payments[..], |sc| {
+            sc.recompute_energy();
+        })
+        .assert_ok();
+
+    let expected_energy = rust_biguint!(half_balance) * (first_unlock_epoch - current_epoch)
+        + rust_biguint!(half_balance) * (second_unlock_epoch - current_epoch);
+    assert_eq!(setup.get_user_energy(&first_user), expected_energy);
+    assert_eq!(setup.get_total_energy(), expected_energy);
+    assert_eq!(setup.get_total_locked_tokens(), rust_biguint!(USER_BALANCE));
+
+    // tokens are returned
+    setup.b_mock.check_nft_balance(
+        &first_user,
+        LOCKED_TOKEN_ID,
+        1,
+        &rust_biguint!(half_balance),
+        Some(&LockedTokenAttributes::<DebugApi> {
+            original_token_id: managed_token_id_wrapped!(BASE_ASSET_TOKEN_ID),
+            original_token_nonce: 0,
+            unlock_epoch: first_unlock_epoch,
+        }),
+    );
+    setup.b_mock.check_nft_balance(
+        &first_user,
+        LOCKED_TOKEN_ID,
+        2,
+        &rust_biguint!(half_balance),
+        Some(&LockedTokenAttributes::<DebugApi> {
+            original_token_id: managed_token_id_wrapped!(BASE_ASSET_TOKEN_ID),
+            original_token_nonce: 0,
+            unlock_epoch: second_unlock_epoch,
+        }),
+    );
+}
+
+#[test]
+fn recompute_energy_received_tokens_test() {
+    let mut setup = SimpleLockEnergySetup::new(energy_factory::contract_obj);
+    let first_user = setup.first_user.clone();
+    let second_user = setup.second_user.clone();
+    let half_balance = USER_BALANCE / 2;
+
+    setup
+        .b_mock
+        .execute_tx(&setup.owner, &setup.sc_wrapper, &rust_biguint!(0), |sc| {
+            sc.set_lock_option_energy_multiplier(LOCK_OPTIONS[2], 12_000);
+        })
+        .assert_ok();
+
+    let mut current_epoch = 1;
+    setup.b_mock.set_block_epoch(current_epoch);
+
+    setup
+        .lock(
+            &first_user,
+            BASE_ASSET_TOKEN_ID,
+            half_balance,
+            LOCK_OPTIONS[2],
+        )
+        .assert_ok();
+    setup
+        .lock(
+            &second_user,
+            BASE_ASSET_TOKEN_ID,
+            half_balance,
+            LOCK_OPTIONS[2],
+        )
+        .assert_ok();
+    let unlock_epoch = to_start_of_month(current_epoch + LOCK_OPTIONS[2]);
+
+    // the second user's tokens share the nonce, and are sent to the first user
+    setup
+        .b_mock
+        .check_nft_balance::<LockedTokenAttributes<DebugApi>>(
+            &second_user,
+            LOCKED_TOKEN_ID,
+            1,
+            &rust_biguint!(half_balance),
+            None,
+        );
+    setup
+        .b_mock
+        .set_nft_balance_all_properties::<LockedTokenAttributes<DebugApi>>(
+            &second_user,
+            LOCKED_TOKEN_ID,
+            1,
+            &rust_biguint!(0),
+            &LockedTokenAttributes::<DebugApi> {
+                original_token_id: managed_token_id_wrapped!(BASE_ASSET_TOKEN_ID),
+                original_token_nonce: 0,
+                unlock_epoch,
+            },
+            0,
+            None,
+            None,
+            None,
+            &[],
+        );
+    setup
+        .b_mock
+        .set_nft_balance_all_properties::<LockedTokenAttributes<DebugApi>>(
+            &first_user,
+            LOCKED_TOKEN_ID,
+            1,
+            &rust_biguint!(USER_BALANCE),
+            &LockedTokenAttributes::<DebugApi> {
+                original_token_id: managed_token_id_wrapped!(BASE_ASSET_TOKEN_ID),
+                original_token_nonce: 0,
+                unlock_epoch,
+            },
+            0,
+            None,
+            None,
+            None,
+            &[],
+        );
+
+    current_epoch = 10;
+    setup.b_mock.set_block_epoch(current_epoch);
+
+    setup
+        .b_mock
+        .execute_esdt_transfer(
+            &first_user,
+            &setup.sc_wrapper,
+            LOCKED_TOKEN_ID,
+            1,
+            &rust_biguint!(USER_BALANCE),
+            |sc| {
+                sc.recompute_energy();
+            },
+        )
+        .assert_ok();
+
+    // only the first user's own tokens keep the multiplier
+    let weighted_amount = rust_biguint!(half_balance) * 12_000u64 / 10_000u64;
+    let expected_energy =
+        (weighted_amount + rust_biguint!(half_balance)) * (unlock_epoch - current_epoch);
+    assert_eq!(setup.get_user_energy(&first_user), expected_energy);
+    assert_eq!(
+        setup.get_total_locked_tokens(),
+        rust_biguint!(USER_BALANCE * 3 / 2)
+    );
+}
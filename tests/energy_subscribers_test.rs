@@ -0,0 +1,110 @@
+#![allow(deprecated)]
+
+mod energy_factory_setup;
+
+use energy_factory::energy_subscribers::EnergySubscribersModule;
+use energy_factory_setup::{energy_subscriber_mock::EnergySubscriberMock, *};
+
+use multiversx_sc_scenario::{managed_address, rust_biguint};
+
+const SUBSCRIBER_GAS_LIMIT: u64 = 10_000_000;
+
+#[test]
+fn energy_subscribers_test() {
+    let mut setup = SimpleLockEnergySetup::new(energy_factory::contract_obj);
+    let owner = setup.owner.clone();
+    let first_user = setup.first_user.clone();
+    let quarter_balance = USER_BALANCE / 4;
+
+    let subscriber_wrapper = setup.b_mock.create_sc_account(
+        &rust_biguint!(0),
+        Some(&owner),
+        EnergySubscriberMock::new,
+        "energy subscriber",
+    );
+    let subscriber_address = subscriber_wrapper.address_ref().clone();
+
+    setup
+        .b_mock
+        .execute_tx(&owner, &setup.sc_wrapper, &rust_biguint!(0), |sc| {
+            sc.add_energy_subscriber(managed_address!(&first_user), SUBSCRIBER_GAS_LIMIT);
+        })
+        .assert_user_error("Invalid SC address");
+    setup
+        .b_mock
+        .execute_tx(&owner, &setup.sc_wrapper, &rust_biguint!(0), |sc| {
+            sc.add_energy_subscriber(managed_address!(&subscriber_address), SUBSCRIBER_GAS_LIMIT);
+        })
+        .assert_ok();
+    setup
+        .b_mock
+        .execute_tx(&owner, &setup.sc_wrapper, &rust_biguint!(0), |sc| {
+            sc.add_energy_subscriber(managed_address!(&subscriber_address), SUBSCRIBER_GAS_LIMIT);
+        })
+        .assert_user_error("Energy subscriber already added");
+
+    setup.b_mock.set_block_epoch(1);
+    setup
+        .lock(
+            &first_user,
+            BASE_ASSET_TOKEN_ID,
+            quarter_balance,
+            LOCK_OPTIONS[0],
+        )
+        .assert_ok();
+
+    let user_energy = setup.get_user_energy(&first_user);
+    setup
+        .b_mock
+        .execute_query(&subscriber_wrapper, |sc| {
+            assert_eq!(sc.notifications_count().get(), 1);
+            assert_eq!(to_rust_biguint(sc.last_energy_amount().get()), user_energy);
+        })
+        .assert_ok();
+
+    // disabled subscribers are not notified
+    setup
+        .b_mock
+        .execute_tx(&owner, &setup.sc_wrapper, &rust_biguint!(0), |sc| {
+            sc.set_energy_subscriber_enabled(managed_address!(&subscriber_address), false);
+        })
+        .assert_ok();
+    setup
+        .lock(
+            &first_user,
+            BASE_ASSET_TOKEN_ID,
+            quarter_balance,
+            LOCK_OPTIONS[0],
+        )
+        .assert_ok();
+    setup
+        .b_mock
+        .execute_query(&subscriber_wrapper, |sc| {
+            assert_eq!(sc.notifications_count().get(), 1);
+        })
+        .assert_ok();
+
+    // neither are removed ones
+    setup
+        .b_mock
+        .execute_tx(&owner, &setup.sc_wrapper, &rust_biguint!(0), |sc| {
+            sc.set_energy_subscriber_enabled(managed_address!(&subscriber_address), true);
+            sc.remove_energy_subscriber(managed_address!(&subscriber_address));
+            assert!(sc.get_energy_subscribers().is_empty());
+        })
+        .assert_ok();
+    setup
+        .lock(
+            &first_user,
+            BASE_ASSET_TOKEN_ID,
+            quarter_balance,
+            LOCK_OPTIONS[0],
+        )
+        .assert_ok();
+    setup
+        .b_mock
+        .execute_query(&subscriber_wrapper, |sc| {
+            assert_eq!(sc.notifications_count().get(), 1);
+        })
+        .assert_ok();
+}
@@ -0,0 +1,314 @@
+#![allow(deprecated)]
+
+mod energy_factory_setup;
+
+use energy_factory::{
+    energy::{Energy, EnergyModule, MAX_USERS_PER_ENERGY_QUERY},
+    migration::SimpleLockMigrationModule,
+    SimpleLockEnergy,
+};
+use energy_factory_setup::*;
+use multiversx_sc::types::{BigInt, MultiValueEncoded};
+use multiversx_sc_modules::pause::PauseModule;
+
+use multiversx_sc_scenario::{managed_address, managed_biguint, rust_biguint};
+
+#[test]
+fn total_energy_test() {
+    let mut setup = SimpleLockEnergySetup::new(energy_factory::contract_obj);
+    let first_user = setup.first_user.clone();
+    let second_user = setup.second_user.clone();
+    let half_balance = USER_BALANCE / 2;
+
+    let mut current_epoch = 1;
+    setup.b_mock.set_block_epoch(current_epoch);
+
+    setup
+        .lock(
+            &first_user,
+            BASE_ASSET_TOKEN_ID,
+            half_balance,
+            LOCK_OPTIONS[0],
+        )
+        .assert_ok();
+    setup
+        .lock(
+            &second_user,
+            BASE_ASSET_TOKEN_ID,
+            half_balance,
+            LOCK_OPTIONS[1],
+        )
+        .assert_ok();
+
+    let first_unlock_epoch = to_start_of_month(current_epoch + LOCK_OPTIONS[0]);
+    let second_unlock_epoch = to_start_of_month(current_epoch + LOCK_OPTIONS[1]);
+    let expected_total_energy = rust_biguint!(half_balance) * (first_unlock_epoch - current_epoch)
+        + rust_biguint!(half_balance) * (second_unlock_epoch - current_epoch);
+    assert_eq!(setup.get_total_energy(), expected_total_energy);
+    assert_eq!(
+        setup.get_total_locked_tokens(),
+        rust_biguint!(2 * half_balance)
+    );
+
+    // total energy decays along with the users' energy
+    current_epoch += 100;
+    setup.b_mock.set_block_epoch(current_epoch);
+
+    let expected_total_energy = rust_biguint!(half_balance) * (first_unlock_epoch - current_epoch)
+        + rust_biguint!(half_balance) * (second_unlock_epoch - current_epoch);
+    assert_eq!(setup.get_total_energy(), expected_total_energy);
+    assert_eq!(
+        setup.get_total_energy(),
+        setup.get_user_energy(&first_user) + setup.get_user_energy(&second_user)
+    );
+
+    // first user unlocks early, only the second user's entry remains
+    setup.unlock_early(&first_user, 1, half_balance).assert_ok();
+
+    let expected_total_energy = rust_biguint!(half_balance) * (second_unlock_epoch - current_epoch);
+    assert_eq!(setup.get_total_energy(), expected_total_energy);
+    assert_eq!(setup.get_total_locked_tokens(), rust_biguint!(half_balance));
+}
+
+#[test]
+fn total_energy_clamped_user_energy_test() {
+    let mut setup = SimpleLockEnergySetup::new(energy_factory::contract_obj);
+    let first_user = setup.first_user.clone();
+    let second_user = setup.second_user.clone();
+    let half_balance = USER_BALANCE / 2;
+
+    let mut current_epoch = 1;
+    setup.b_mock.set_block_epoch(current_epoch);
+
+    // entry saved before the total energy existed, with energy left for 10 epochs
+    setup
+        .b_mock
+        .execute_tx(&setup.owner, &setup.sc_wrapper, &rust_biguint!(0), |sc| {
+            sc.user_energy(&managed_address!(&first_user))
+                .set(Energy::new(
+                    BigInt::from(managed_biguint!(half_balance) * 10u64),
+                    current_epoch,
+                    managed_biguint!(half_balance),
+                ));
+
+            let mut users = MultiValueEncoded::new();
+            users.push(managed_address!(&first_user));
+            sc.add_users_to_total_energy(users.clone());
+
+            // users already included are skipped
+            sc.add_users_to_total_energy(users);
+        })
+        .assert_ok();
+
+    assert_eq!(
+        setup.get_total_energy(),
+        rust_biguint!(half_balance) * 10u64
+    );
+
+    setup
+        .lock(
+            &second_user,
+            BASE_ASSET_TOKEN_ID,
+            half_balance,
+            LOCK_OPTIONS[0],
+        )
+        .assert_ok();
+
+    current_epoch += 5;
+    setup.b_mock.set_block_epoch(current_epoch);
+    assert_eq!(
+        setup.get_user_energy(&first_user),
+        rust_biguint!(half_balance) * 5u64
+    );
+    assert_eq!(
+        setup.get_total_energy(),
+        setup.get_user_energy(&first_user) + setup.get_user_energy(&second_user)
+    );
+
+    // the first user's energy is clamped at zero, and so is their part of the total
+    current_epoch += 15;
+    setup.b_mock.set_block_epoch(current_epoch);
+    assert_eq!(setup.get_user_energy(&first_user), rust_biguint!(0));
+    assert_eq!(
+        setup.get_total_energy(),
+        setup.get_user_energy(&second_user)
+    );
+
+    setup
+        .lock(
+            &first_user,
+            BASE_ASSET_TOKEN_ID,
+            half_balance,
+            LOCK_OPTIONS[1],
+        )
+        .assert_ok();
+    assert_eq!(
+        setup.get_total_energy(),
+        setup.get_user_energy(&first_user) + setup.get_user_energy(&second_user)
+    );
+
+    current_epoch += 100;
+    setup.b_mock.set_block_epoch(current_epoch);
+    assert_eq!(
+        setup.get_total_energy(),
+        setup.get_user_energy(&first_user) + setup.get_user_energy(&second_user)
+    );
+}
+
+#[test]
+fn total_energy_after_upgrade_test() {
+    let mut setup = SimpleLockEnergySetup::new(energy_factory::contract_obj);
+    let first_user = setup.first_user.clone();
+    let second_user = setup.second_user.clone();
+    let half_balance = USER_BALANCE / 2;
+
+    let current_epoch = 1;
+    setup.b_mock.set_block_epoch(current_epoch);
+
+    // entries saved before the total energy existed
+    setup
+        .b_mock
+        .execute_tx(&setup.owner, &setup.sc_wrapper, &rust_biguint!(0), |sc| {
+            for user in [&first_user, &second_user] {
+                sc.user_energy(&managed_address!(user)).set(Energy::new(
+                    BigInt::from(managed_biguint!(half_balance) * 100u64),
+                    current_epoch,
+                    managed_biguint!(half_balance),
+                ));
+            }
+            sc.total_energy().clear();
+
+            sc.upgrade();
+            assert!(sc.is_paused());
+
+            let mut users = MultiValueEncoded::new();
+            users.push(managed_address!(&first_user));
+            sc.add_users_to_total_energy(users);
+
+            sc.set_paused(false);
+        })
+        .assert_ok();
+
+    assert_eq!(setup.get_total_energy(), setup.get_user_energy(&first_user));
+    assert_eq!(setup.get_total_locked_tokens(), rust_biguint!(half_balance));
+
+    // a user not yet added is included on their next update, without failing
+    setup
+        .lock(
+            &second_user,
+            BASE_ASSET_TOKEN_ID,
+            half_balance,
+            LOCK_OPTIONS[0],
+        )
+        .assert_ok();
+    assert_eq!(
+        setup.get_total_energy(),
+        setup.get_user_energy(&first_user) + setup.get_user_energy(&second_user)
+    );
+    assert_eq!(
+        setup.get_total_locked_tokens(),
+        rust_biguint!(3 * half_balance)
+    );
+}
+
+#[test]
+fn energy_for_multiple_users_test() {
+    let mut setup = SimpleLockEnergySetup::new(energy_factory::contract_obj);
+    let first_user = setup.first_user.clone();
+    let second_user = setup.second_user.clone();
+    let half_balance = USER_BALANCE / 2;
+
+    let current_epoch = 1;
+    setup.b_mock.set_block_epoch(current_epoch);
+
+    setup
+        .lock(
+            &first_user,
+            BASE_ASSET_TOKEN_ID,
+            half_balance,
+            LOCK_OPTIONS[0],
+        )
+        .assert_ok();
+    setup
+        .lock(
+            &second_user,
+            BASE_ASSET_TOKEN_ID,
+            half_balance,
+            LOCK_OPTIONS[1],
+        )
+        .assert_ok();
+
+    setup.b_mock.set_block_epoch(current_epoch + 10);
+
+    let first_user_energy = setup.get_user_energy(&first_user);
+    let second_user_energy = setup.get_user_energy(&second_user);
+    setup
+        .b_mock
+        .execute_query(&setup.sc_wrapper, |sc| {
+            let mut users = MultiValueEncoded::new();
+            users.push(managed_address!(&second_user));
+            users.push(managed_address!(&first_user));
+
+            let entries: Vec<_> = sc
+                .get_energy_entries_for_users(users.clone())
+                .into_iter()
+                .collect();
+            assert_eq!(entries.len(), 2);
+            assert_eq!(
+                entries[0],
+                sc.get_updated_energy_entry_for_user(&managed_address!(&second_user))
+            );
+            assert_eq!(
+                entries[1],
+                sc.get_updated_energy_entry_for_user(&managed_address!(&first_user))
+            );
+
+            let amounts: Vec<_> = sc.get_energy_amounts_for_users(users).into_iter().collect();
+            assert_eq!(amounts.len(), 2);
+            assert_eq!(to_rust_biguint(amounts[0].clone()), second_user_energy);
+            assert_eq!(to_rust_biguint(amounts[1].clone()), first_user_energy);
+        })
+        .assert_ok();
+}
+
+#[test]
+fn energy_for_too_many_users_test() {
+    let mut setup = SimpleLockEnergySetup::new(energy_factory::contract_obj);
+    let first_user = setup.first_user.clone();
+
+    setup
+        .b_mock
+        .execute_query(&setup.sc_wrapper, |sc| {
+            let mut users = MultiValueEncoded::new();
+            for _ in 0..MAX_USERS_PER_ENERGY_QUERY {
+                users.push(managed_address!(&first_user));
+            }
+            assert_eq!(
+                sc.get_energy_amounts_for_users(users).len(),
+                MAX_USERS_PER_ENERGY_QUERY
+            );
+        })
+        .assert_ok();
+
+    setup
+        .b_mock
+        .execute_query(&setup.sc_wrapper, |sc| {
+            let mut users = MultiValueEncoded::new();
+            for _ in 0..=MAX_USERS_PER_ENERGY_QUERY {
+                users.push(managed_address!(&first_user));
+            }
+            let _ = sc.get_energy_entries_for_users(users);
+        })
+        .assert_user_error("Too many users");
+
+    setup
+        .b_mock
+        .execute_query(&setup.sc_wrapper, |sc| {
+            let mut users = MultiValueEncoded::new();
+            for _ in 0..=MAX_USERS_PER_ENERGY_QUERY {
+                users.push(managed_address!(&first_user));
+            }
+            let _ = sc.get_energy_amounts_for_users(users);
+        })
+        .assert_user_error("Too many users");
+}
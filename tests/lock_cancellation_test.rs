@@ -0,0 +1,207 @@
+#![allow(deprecated)]
+
+mod energy_factory_setup;
+
+use energy_factory::{lock_cancellation::LockCancellationModule, SimpleLockEnergy};
+use energy_factory_setup::*;
+
+use multiversx_sc::codec::multi_types::OptionalValue;
+use multiversx_sc_scenario::{managed_address, managed_biguint, rust_biguint};
+
+#[test]
+fn lock_cancellation_test() {
+    let mut setup = SimpleLockEnergySetup::new(energy_factory::contract_obj);
+    let owner = setup.owner.clone();
+    let first_user = setup.first_user.clone();
+    let second_user = setup.second_user.clone();
+    let quarter_balance = USER_BALANCE / 4;
+
+    setup
+        .b_mock
+        .execute_tx(&owner, &setup.sc_wrapper, &rust_biguint!(0), |sc| {
+            sc.set_lock_cancellation_grace_period(31);
+        })
+        .assert_user_error("Invalid grace period");
+    setup
+        .b_mock
+        .execute_tx(&owner, &setup.sc_wrapper, &rust_biguint!(0), |sc| {
+            sc.set_lock_cancellation_grace_period(3);
+        })
+        .assert_ok();
+
+    // creates nonce 1, unlocking at epoch 360
+    setup.b_mock.set_block_epoch(10);
+    setup
+        .lock(
+            &first_user,
+            BASE_ASSET_TOKEN_ID,
+            quarter_balance,
+            LOCK_OPTIONS[0],
+        )
+        .assert_ok();
+    setup
+        .b_mock
+        .execute_query(&setup.sc_wrapper, |sc| {
+            assert_eq!(
+                sc.get_cancellable_amount(managed_address!(&first_user), 1),
+                managed_biguint!(quarter_balance)
+            );
+            assert_eq!(
+                sc.get_cancellable_amount(managed_address!(&second_user), 1),
+                managed_biguint!(0)
+            );
+        })
+        .assert_ok();
+    assert!(setup.get_user_energy(&first_user) > rust_biguint!(0));
+
+    // cancel within the grace period, without any penalty
+    setup.b_mock.set_block_epoch(13);
+    setup
+        .cancel_lock(&first_user, 1, quarter_balance)
+        .assert_ok();
+    setup.b_mock.check_esdt_balance(
+        &first_user,
+        BASE_ASSET_TOKEN_ID,
+        &rust_biguint!(USER_BALANCE),
+    );
+    assert_eq!(setup.get_user_energy(&first_user), rust_biguint!(0));
+
+    // the grace period starts at each user's own lock, even if the nonce is reused
+    setup
+        .lock(
+            &second_user,
+            BASE_ASSET_TOKEN_ID,
+            quarter_balance,
+            LOCK_OPTIONS[0],
+        )
+        .assert_ok();
+    setup
+        .lock(
+            &first_user,
+            BASE_ASSET_TOKEN_ID,
+            quarter_balance,
+            LOCK_OPTIONS[0],
+        )
+        .assert_ok();
+    setup.b_mock.set_block_epoch(16);
+    setup
+        .cancel_lock(&second_user, 1, quarter_balance)
+        .assert_ok();
+
+    // the grace period is not extended by further locks into the same nonce
+    setup
+        .lock(
+            &first_user,
+            BASE_ASSET_TOKEN_ID,
+            quarter_balance,
+            LOCK_OPTIONS[0],
+        )
+        .assert_ok();
+    setup.b_mock.set_block_epoch(17);
+    setup
+        .cancel_lock(&first_user, 1, quarter_balance)
+        .assert_user_error("Lock may not be cancelled");
+
+    // creates nonce 2, unlocking at epoch 720
+    setup.b_mock.set_block_epoch(20);
+    setup
+        .lock(
+            &first_user,
+            BASE_ASSET_TOKEN_ID,
+            quarter_balance,
+            LOCK_OPTIONS[1],
+        )
+        .assert_ok();
+
+    // another user extending tokens into the nonce does not affect the first user's lock,
+    // while the extended tokens may not be cancelled
+    setup
+        .lock(
+            &second_user,
+            BASE_ASSET_TOKEN_ID,
+            quarter_balance,
+            LOCK_OPTIONS[0],
+        )
+        .assert_ok();
+    setup
+        .extend_locking_period(
+            &second_user,
+            LOCKED_TOKEN_ID,
+            1,
+            quarter_balance,
+            LOCK_OPTIONS[1],
+            None,
+        )
+        .assert_ok();
+    setup
+        .cancel_lock(&second_user, 2, quarter_balance)
+        .assert_user_error("Lock may not be cancelled");
+
+    // part of the locked amount may be cancelled
+    setup
+        .cancel_lock(&first_user, 2, quarter_balance / 2)
+        .assert_ok();
+    setup
+        .b_mock
+        .execute_query(&setup.sc_wrapper, |sc| {
+            assert_eq!(
+                sc.get_cancellable_amount(managed_address!(&first_user), 2),
+                managed_biguint!(quarter_balance / 2)
+            );
+        })
+        .assert_ok();
+}
+
+#[test]
+fn lock_for_other_address_cancellation_test() {
+    let mut setup = SimpleLockEnergySetup::new(energy_factory::contract_obj);
+    let owner = setup.owner.clone();
+    let first_user = setup.first_user.clone();
+    let second_user = setup.second_user.clone();
+    let quarter_balance = USER_BALANCE / 4;
+
+    setup
+        .b_mock
+        .execute_tx(&owner, &setup.sc_wrapper, &rust_biguint!(0), |sc| {
+            sc.set_lock_cancellation_grace_period(3);
+        })
+        .assert_ok();
+
+    // creates nonce 1 for the second user, unlocking at epoch 360
+    setup.b_mock.set_block_epoch(10);
+    setup
+        .b_mock
+        .execute_esdt_transfer(
+            &first_user,
+            &setup.sc_wrapper,
+            BASE_ASSET_TOKEN_ID,
+            0,
+            &rust_biguint!(quarter_balance),
+            |sc| {
+                sc.lock_tokens_endpoint(
+                    LOCK_OPTIONS[0],
+                    OptionalValue::Some(managed_address!(&second_user)),
+                );
+            },
+        )
+        .assert_ok();
+    setup
+        .b_mock
+        .execute_query(&setup.sc_wrapper, |sc| {
+            assert_eq!(
+                sc.get_cancellable_amount(managed_address!(&first_user), 1),
+                managed_biguint!(0)
+            );
+            assert_eq!(
+                sc.get_cancellable_amount(managed_address!(&second_user), 1),
+                managed_biguint!(0)
+            );
+        })
+        .assert_ok();
+
+    // the recipient may not cancel a lock they did not pay for
+    setup
+        .cancel_lock(&second_user, 1, quarter_balance)
+        .assert_user_error("Lock may not be cancelled");
+    assert!(setup.get_user_energy(&second_user) > rust_biguint!(0));
+}
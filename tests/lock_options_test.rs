@@ -0,0 +1,690 @@
+#![allow(deprecated)]
+
+mod energy_factory_setup;
+
+use energy_factory::{
+    lock_options::{
+        AllLockOptions, LockOption, LockOptionAvailability, LockOptionsModule,
+        UnlockEpochRoundingMode,
+    },
+    lock_options_endpoints::{LockOptionsEndpointsModule, MIN_LOCK_OPTIONS_SCHEDULE_DELAY},
+    penalty::{to_penalty_precision, LocalPenaltyModule, PENALTY_PRECISION},
+    SimpleLockEnergy,
+};
+use energy_factory_setup::*;
+use multiversx_sc::types::MultiValueEncoded;
+use simple_lock::locked_token::LockedTokenAttributes;
+
+use multiversx_sc_scenario::{managed_token_id_wrapped, rust_biguint, DebugApi};
+
+#[test]
+fn remove_and_update_lock_options_test() {
+    let mut setup = SimpleLockEnergySetup::new(energy_factory::contract_obj);
+    let owner = setup.owner.clone();
+    let first_user = setup.first_user.clone();
+    let half_balance = USER_BALANCE / 2;
+
+    setup.b_mock.set_block_epoch(0);
+    setup
+        .lock(
+            &first_user,
+            BASE_ASSET_TOKEN_ID,
+            half_balance,
+            LOCK_OPTIONS[2],
+        )
+        .assert_ok();
+
+    setup
+        .b_mock
+        .execute_tx(&owner, &setup.sc_wrapper, &rust_biguint!(0), |sc| {
+            let mut to_remove = MultiValueEncoded::new();
+            to_remove.push(100);
+            sc.remove_lock_options(to_remove);
+        })
+        .assert_user_error("Lock option not found");
+    setup
+        .b_mock
+        .execute_tx(&owner, &setup.sc_wrapper, &rust_biguint!(0), |sc| {
+            let mut to_remove = MultiValueEncoded::new();
+            for option in LOCK_OPTIONS {
+                to_remove.push(*option);
+            }
+            sc.remove_lock_options(to_remove);
+        })
+        .assert_user_error("Must keep at least one lock option");
+    setup
+        .b_mock
+        .execute_tx(&owner, &setup.sc_wrapper, &rust_biguint!(0), |sc| {
+            let mut to_remove = MultiValueEncoded::new();
+            to_remove.push(LOCK_OPTIONS[2]);
+            sc.remove_lock_options(to_remove);
+        })
+        .assert_ok();
+
+    // removed options can no longer be chosen
+    setup
+        .lock(
+            &first_user,
+            BASE_ASSET_TOKEN_ID,
+            half_balance,
+            LOCK_OPTIONS[2],
+        )
+        .assert_user_error("Invalid lock choice");
+
+    setup
+        .b_mock
+        .execute_tx(&owner, &setup.sc_wrapper, &rust_biguint!(0), |sc| {
+            sc.update_lock_option_penalty(LOCK_OPTIONS[1], 3_000);
+        })
+        .assert_user_error("Invalid lock option percentages");
+    setup
+        .b_mock
+        .execute_tx(&owner, &setup.sc_wrapper, &rust_biguint!(0), |sc| {
+            sc.update_lock_option_penalty(LOCK_OPTIONS[1], 5_000);
+        })
+        .assert_ok();
+
+    // tokens locked for longer than the max option get the max option's penalty
+    let expected_penalty_amount = rust_biguint!(half_balance) * 5_000u64 / 10_000u64;
+    let penalty_amount = setup.get_penalty_amount(half_balance, LOCK_OPTIONS[2], 0);
+    assert_eq!(penalty_amount, expected_penalty_amount);
+
+    setup.unlock_early(&first_user, 1, half_balance).assert_ok();
+    setup.b_mock.check_esdt_balance(
+        &first_user,
+        BASE_ASSET_TOKEN_ID,
+        &(rust_biguint!(USER_BALANCE) - expected_penalty_amount),
+    );
+}
+
+#[test]
+fn lock_option_availability_test() {
+    let mut setup = SimpleLockEnergySetup::new(energy_factory::contract_obj);
+    let owner = setup.owner.clone();
+    let first_user = setup.first_user.clone();
+    let quarter_balance = USER_BALANCE / 4;
+
+    setup
+        .b_mock
+        .execute_tx(&owner, &setup.sc_wrapper, &rust_biguint!(0), |sc| {
+            sc.set_lock_option_availability(LOCK_OPTIONS[2], Some(20), Some(10));
+        })
+        .assert_user_error("Invalid availability window");
+    setup
+        .b_mock
+        .execute_tx(&owner, &setup.sc_wrapper, &rust_biguint!(0), |sc| {
+            sc.set_lock_option_availability(LOCK_OPTIONS[2], Some(10), Some(20));
+        })
+        .assert_ok();
+
+    // before the window
+    setup.b_mock.set_block_epoch(5);
+    setup
+        .lock(
+            &first_user,
+            BASE_ASSET_TOKEN_ID,
+            quarter_balance,
+            LOCK_OPTIONS[2],
+        )
+        .assert_user_error("Invalid lock choice");
+    setup
+        .b_mock
+        .execute_query(&setup.sc_wrapper, |sc| {
+            let lock_options = sc.get_lock_options_view();
+            assert_eq!(lock_options.len(), LOCK_OPTIONS.len());
+            assert_eq!(lock_options[2].lock_epochs, LOCK_OPTIONS[2]);
+
+            let options_status: Vec<(LockOption, LockOptionAvailability, bool)> = sc
+                .get_lock_options_with_availability()
+                .into_iter()
+                .map(|entry| entry.into_tuple())
+                .collect();
+            assert_eq!(options_status.len(), LOCK_OPTIONS.len());
+            assert!(options_status[0].2);
+            assert!(options_status[1].2);
+            assert!(!options_status[2].2);
+            assert_eq!(options_status[0].1, LockOptionAvailability::default());
+            assert_eq!(
+                options_status[2].1,
+                LockOptionAvailability {
+                    available_from_epoch: Some(10),
+                    available_until_epoch: Some(20),
+                }
+            );
+        })
+        .assert_ok();
+
+    // inside the window, both ends included
+    for epoch in [10, 20] {
+        setup.b_mock.set_block_epoch(epoch);
+        setup
+            .lock(
+                &first_user,
+                BASE_ASSET_TOKEN_ID,
+                quarter_balance,
+                LOCK_OPTIONS[2],
+            )
+            .assert_ok();
+    }
+
+    // after the window, the option is still used for penalties
+    setup.b_mock.set_block_epoch(21);
+    setup
+        .lock(
+            &first_user,
+            BASE_ASSET_TOKEN_ID,
+            quarter_balance,
+            LOCK_OPTIONS[2],
+        )
+        .assert_user_error("Invalid lock choice");
+
+    let expected_penalty_amount =
+        rust_biguint!(quarter_balance) * PENALTY_PERCENTAGES[2] / 10_000u64;
+    let penalty_amount = setup.get_penalty_amount(quarter_balance, LOCK_OPTIONS[2], 0);
+    assert_eq!(penalty_amount, expected_penalty_amount);
+}
+
+#[test]
+fn legacy_lock_options_migration_test() {
+    let mut setup = SimpleLockEnergySetup::new(energy_factory::contract_obj);
+    let owner = setup.owner.clone();
+
+    setup
+        .b_mock
+        .execute_tx(&owner, &setup.sc_wrapper, &rust_biguint!(0), |sc| {
+            let mut legacy_options = AllLockOptions::new();
+            for (option, penalty) in LOCK_OPTIONS.iter().zip(PENALTY_PERCENTAGES.iter()) {
+                legacy_options.push(LockOption {
+                    lock_epochs: *option,
+                    penalty_start_percentage: *penalty,
+                });
+            }
+            sc.legacy_lock_options().set(legacy_options);
+            sc.lock_options().clear();
+
+            sc.upgrade();
+
+            assert!(sc.legacy_lock_options().is_empty());
+            let lock_options = sc.lock_options();
+            assert_eq!(lock_options.len(), LOCK_OPTIONS.len());
+            for (i, option) in lock_options.iter().enumerate() {
+                assert_eq!(option.lock_epochs, LOCK_OPTIONS[i]);
+                assert_eq!(option.penalty_start_percentage, PENALTY_PERCENTAGES[i]);
+            }
+        })
+        .assert_ok();
+}
+
+#[test]
+fn unlock_epoch_rounding_test() {
+    let mut setup = SimpleLockEnergySetup::new(energy_factory::contract_obj);
+    let owner = setup.owner.clone();
+    let first_user = setup.first_user.clone();
+    let quarter_balance = USER_BALANCE / 4;
+
+    let current_epoch = 1;
+    setup.b_mock.set_block_epoch(current_epoch);
+
+    setup
+        .b_mock
+        .execute_tx(&owner, &setup.sc_wrapper, &rust_biguint!(0), |sc| {
+            sc.set_unlock_epoch_granularity(0);
+        })
+        .assert_user_error("Invalid unlock epoch granularity");
+    setup
+        .b_mock
+        .execute_tx(&owner, &setup.sc_wrapper, &rust_biguint!(0), |sc| {
+            sc.set_unlock_epoch_granularity(EPOCHS_IN_WEEK);
+        })
+        .assert_ok();
+
+    // unlock epoch 361 -> rounded to weeks
+    let expected_unlock_epochs = [
+        (UnlockEpochRoundingMode::Floor, 357),
+        (UnlockEpochRoundingMode::Ceil, 364),
+        (UnlockEpochRoundingMode::None, 361),
+    ];
+    for (i, (rounding_mode, expected_unlock_epoch)) in expected_unlock_epochs.iter().enumerate() {
+        setup
+            .b_mock
+            .execute_tx(&owner, &setup.sc_wrapper, &rust_biguint!(0), |sc| {
+                sc.set_unlock_epoch_rounding_mode(*rounding_mode);
+            })
+            .assert_ok();
+        setup
+            .lock(
+                &first_user,
+                BASE_ASSET_TOKEN_ID,
+                quarter_balance,
+                LOCK_OPTIONS[0],
+            )
+            .assert_ok();
+
+        setup.b_mock.check_nft_balance(
+            &first_user,
+            LOCKED_TOKEN_ID,
+            i as u64 + 1,
+            &rust_biguint!(quarter_balance),
+            Some(&LockedTokenAttributes::<DebugApi> {
+                original_token_id: managed_token_id_wrapped!(BASE_ASSET_TOKEN_ID),
+                original_token_nonce: 0,
+                unlock_epoch: *expected_unlock_epoch,
+            }),
+        );
+    }
+}
+
+#[test]
+fn lock_range_mode_test() {
+    let mut setup = SimpleLockEnergySetup::new(energy_factory::contract_obj);
+    let owner = setup.owner.clone();
+    let first_user = setup.first_user.clone();
+    let quarter_balance = USER_BALANCE / 4;
+    let custom_lock_epochs = 500;
+
+    let current_epoch = 0;
+    setup.b_mock.set_block_epoch(current_epoch);
+
+    setup
+        .lock(
+            &first_user,
+            BASE_ASSET_TOKEN_ID,
+            quarter_balance,
+            custom_lock_epochs,
+        )
+        .assert_user_error("Invalid lock choice");
+
+    setup
+        .b_mock
+        .execute_tx(&owner, &setup.sc_wrapper, &rust_biguint!(0), |sc| {
+            sc.set_lock_range_mode(true);
+            sc.set_lock_option_energy_multiplier(LOCK_OPTIONS[0], 12_000);
+        })
+        .assert_ok();
+
+    // outside the range
+    setup
+        .lock(
+            &first_user,
+            BASE_ASSET_TOKEN_ID,
+            quarter_balance,
+            LOCK_OPTIONS[0] - 1,
+        )
+        .assert_user_error("Invalid lock choice");
+    setup
+        .lock(
+            &first_user,
+            BASE_ASSET_TOKEN_ID,
+            quarter_balance,
+            LOCK_OPTIONS[2] + 1,
+        )
+        .assert_user_error("Invalid lock choice");
+
+    // custom duration gets the multiplier of the longest option below it
+    setup
+        .lock(
+            &first_user,
+            BASE_ASSET_TOKEN_ID,
+            quarter_balance,
+            custom_lock_epochs,
+        )
+        .assert_ok();
+    let unlock_epoch = to_start_of_month(current_epoch + custom_lock_epochs);
+    assert_eq!(
+        setup.get_user_energy(&first_user),
+        rust_biguint!(quarter_balance) * 12u64 / 10u64 * (unlock_epoch - current_epoch)
+    );
+
+    // penalty is interpolated between the surrounding options
+    let remaining_epochs = unlock_epoch - current_epoch;
+    let expected_penalty_percentage = to_penalty_precision(PENALTY_PERCENTAGES[0])
+        + to_penalty_precision(PENALTY_PERCENTAGES[1] - PENALTY_PERCENTAGES[0])
+            * (remaining_epochs - LOCK_OPTIONS[0])
+            / (LOCK_OPTIONS[1] - LOCK_OPTIONS[0]);
+    let penalty_amount = setup.get_penalty_amount(quarter_balance, remaining_epochs, 0);
+    assert_eq!(
+        penalty_amount,
+        rust_biguint!(quarter_balance) * expected_penalty_percentage / PENALTY_PRECISION
+    );
+
+    // reducing to a custom duration is allowed as well
+    setup
+        .lock(
+            &first_user,
+            BASE_ASSET_TOKEN_ID,
+            quarter_balance,
+            LOCK_OPTIONS[2],
+        )
+        .assert_ok();
+    let reduce_penalty_amount =
+        setup.get_penalty_amount(quarter_balance, LOCK_OPTIONS[2], remaining_epochs);
+    setup
+        .reduce_lock_period(&first_user, 2, quarter_balance, custom_lock_epochs)
+        .assert_ok();
+
+    // same attributes as the first position, so the same nonce is used
+    setup.b_mock.check_nft_balance(
+        &first_user,
+        LOCKED_TOKEN_ID,
+        1,
+        &(rust_biguint!(2 * quarter_balance) - reduce_penalty_amount),
+        Some(&LockedTokenAttributes::<DebugApi> {
+            original_token_id: managed_token_id_wrapped!(BASE_ASSET_TOKEN_ID),
+            original_token_nonce: 0,
+            unlock_epoch,
+        }),
+    );
+}
+
+#[test]
+fn many_lock_options_test() {
+    let mut setup = SimpleLockEnergySetup::new(energy_factory::contract_obj);
+    let owner = setup.owner.clone();
+    let first_user = setup.first_user.clone();
+    let quarter_balance = USER_BALANCE / 4;
+
+    // monthly options between the existing ones, added out of order
+    let mut monthly_options = Vec::new();
+    for i in 1..12 {
+        monthly_options.push((LOCK_OPTIONS[0] + i * 30, PENALTY_PERCENTAGES[0] + i * 150));
+    }
+    for i in 1..24 {
+        monthly_options.push((LOCK_OPTIONS[1] + i * 30, PENALTY_PERCENTAGES[1] + i * 80));
+    }
+    for i in 1..14 {
+        monthly_options.push((LOCK_OPTIONS[2] + i * 30, PENALTY_PERCENTAGES[2] + i * 100));
+    }
+    let total_options = LOCK_OPTIONS.len() + monthly_options.len();
+
+    setup
+        .b_mock
+        .execute_tx(&owner, &setup.sc_wrapper, &rust_biguint!(0), |sc| {
+            let mut new_options = MultiValueEncoded::new();
+            for (lock_epochs, penalty) in monthly_options.iter().rev() {
+                new_options.push((*lock_epochs, *penalty).into());
+            }
+            sc.add_lock_options(new_options);
+
+            let lock_options = sc.lock_options();
+            assert_eq!(lock_options.len(), total_options);
+            for i in 1..lock_options.len() {
+                let option = lock_options.get(i);
+                let next_option = lock_options.get(i + 1);
+                assert!(option.lock_epochs < next_option.lock_epochs);
+                assert!(option.penalty_start_percentage < next_option.penalty_start_percentage);
+            }
+        })
+        .assert_ok();
+
+    setup
+        .b_mock
+        .execute_tx(&owner, &setup.sc_wrapper, &rust_biguint!(0), |sc| {
+            let mut new_options = MultiValueEncoded::new();
+            new_options.push((LOCK_OPTIONS[1] + 30, PENALTY_PERCENTAGES[1] + 80).into());
+            sc.add_lock_options(new_options);
+        })
+        .assert_user_error("Duplicate lock options");
+    setup
+        .b_mock
+        .execute_tx(&owner, &setup.sc_wrapper, &rust_biguint!(0), |sc| {
+            let mut new_options = MultiValueEncoded::new();
+            new_options.push((LOCK_OPTIONS[1] + 15, PENALTY_PERCENTAGES[1] + 100).into());
+            sc.add_lock_options(new_options);
+        })
+        .assert_user_error("Invalid lock option percentages");
+
+    // penalties use the closest options
+    let lock_epochs = LOCK_OPTIONS[1] + 9 * 30;
+    let penalty_amount = setup.get_penalty_amount(quarter_balance, lock_epochs, 0);
+    assert_eq!(
+        penalty_amount,
+        rust_biguint!(quarter_balance) * (PENALTY_PERCENTAGES[1] + 9 * 80) / 10_000u64
+    );
+
+    setup.b_mock.set_block_epoch(0);
+    setup
+        .lock(
+            &first_user,
+            BASE_ASSET_TOKEN_ID,
+            quarter_balance,
+            lock_epochs,
+        )
+        .assert_ok();
+
+    // removing an option keeps the rest sorted
+    setup
+        .b_mock
+        .execute_tx(&owner, &setup.sc_wrapper, &rust_biguint!(0), |sc| {
+            let mut to_remove = MultiValueEncoded::new();
+            to_remove.push(lock_epochs);
+            sc.remove_lock_options(to_remove);
+
+            let lock_options = sc.lock_options();
+            assert_eq!(lock_options.len(), total_options - 1);
+            for i in 1..lock_options.len() {
+                assert!(lock_options.get(i).lock_epochs < lock_options.get(i + 1).lock_epochs);
+            }
+        })
+        .assert_ok();
+    setup
+        .lock(
+            &first_user,
+            BASE_ASSET_TOKEN_ID,
+            quarter_balance,
+            lock_epochs,
+        )
+        .assert_user_error("Invalid lock choice");
+
+    let expected_penalty_percentage = PENALTY_PERCENTAGES[1] + 8 * 80 + 2 * 80 / 2;
+    let penalty_amount = setup.get_penalty_amount(quarter_balance, lock_epochs, 0);
+    assert_eq!(
+        penalty_amount,
+        rust_biguint!(quarter_balance) * expected_penalty_percentage / 10_000u64
+    );
+}
+
+#[test]
+fn scheduled_lock_options_test() {
+    let mut setup = SimpleLockEnergySetup::new(energy_factory::contract_obj);
+    let owner = setup.owner.clone();
+    let first_user = setup.first_user.clone();
+    let new_lock_options = [(EPOCHS_IN_YEAR, 2_000), (4 * EPOCHS_IN_YEAR, 5_000)];
+
+    setup
+        .b_mock
+        .execute_tx(&owner, &setup.sc_wrapper, &rust_biguint!(0), |sc| {
+            sc.set_lock_option_energy_multiplier(LOCK_OPTIONS[0], 12_000);
+            sc.set_lock_option_energy_multiplier(LOCK_OPTIONS[1], 15_000);
+            sc.set_lock_option_availability(LOCK_OPTIONS[0], Some(5), None);
+            sc.set_lock_option_availability(LOCK_OPTIONS[1], Some(5), None);
+        })
+        .assert_ok();
+
+    setup.b_mock.set_block_epoch(10);
+    setup
+        .b_mock
+        .execute_tx(&owner, &setup.sc_wrapper, &rust_biguint!(0), |sc| {
+            let mut lock_options = MultiValueEncoded::new();
+            for (lock_epochs, penalty) in new_lock_options {
+                lock_options.push((lock_epochs, penalty).into());
+            }
+            sc.schedule_lock_options(16, lock_options);
+        })
+        .assert_user_error("Effective epoch too early");
+    setup
+        .b_mock
+        .execute_tx(&owner, &setup.sc_wrapper, &rust_biguint!(0), |sc| {
+            let mut lock_options = MultiValueEncoded::new();
+            for (lock_epochs, penalty) in new_lock_options.iter().rev() {
+                lock_options.push((*lock_epochs, *penalty).into());
+            }
+            sc.schedule_lock_options(20, lock_options);
+        })
+        .assert_user_error("Lock options must be sorted");
+
+    // schedule, then cancel before activation
+    setup
+        .b_mock
+        .execute_tx(&owner, &setup.sc_wrapper, &rust_biguint!(0), |sc| {
+            let mut lock_options = MultiValueEncoded::new();
+            for (lock_epochs, penalty) in new_lock_options {
+                lock_options.push((lock_epochs, penalty).into());
+            }
+            sc.schedule_lock_options(20, lock_options);
+        })
+        .assert_ok();
+    setup
+        .b_mock
+        .execute_query(&setup.sc_wrapper, |sc| {
+            assert_eq!(sc.get_scheduled_lock_options_epoch(), Some(20));
+            assert_eq!(sc.get_scheduled_lock_options_view().len(), 2);
+            assert_eq!(sc.lock_options().len(), LOCK_OPTIONS.len());
+        })
+        .assert_ok();
+    setup
+        .b_mock
+        .execute_tx(&owner, &setup.sc_wrapper, &rust_biguint!(0), |sc| {
+            sc.cancel_scheduled_lock_options();
+        })
+        .assert_ok();
+    setup
+        .b_mock
+        .execute_query(&setup.sc_wrapper, |sc| {
+            assert_eq!(sc.get_scheduled_lock_options_epoch(), None);
+            assert!(sc.get_scheduled_lock_options_view().is_empty());
+        })
+        .assert_ok();
+    setup
+        .b_mock
+        .execute_tx(&owner, &setup.sc_wrapper, &rust_biguint!(0), |sc| {
+            sc.cancel_scheduled_lock_options();
+        })
+        .assert_user_error("No scheduled lock options change");
+
+    // schedule again, and let it activate
+    setup
+        .b_mock
+        .execute_tx(&owner, &setup.sc_wrapper, &rust_biguint!(0), |sc| {
+            let mut lock_options = MultiValueEncoded::new();
+            for (lock_epochs, penalty) in new_lock_options {
+                lock_options.push((lock_epochs, penalty).into());
+            }
+            sc.schedule_lock_options(20, lock_options);
+        })
+        .assert_ok();
+    setup
+        .lock(
+            &first_user,
+            BASE_ASSET_TOKEN_ID,
+            USER_BALANCE / 2,
+            LOCK_OPTIONS[1],
+        )
+        .assert_ok();
+
+    setup.b_mock.set_block_epoch(20);
+    setup
+        .b_mock
+        .execute_query(&setup.sc_wrapper, |sc| {
+            assert_eq!(sc.get_scheduled_lock_options_epoch(), None);
+            assert_eq!(sc.lock_options().len(), 2);
+            assert_eq!(
+                sc.calculate_penalty_percentage_full_unlock(EPOCHS_IN_YEAR),
+                to_penalty_precision(2_000)
+            );
+        })
+        .assert_ok();
+    setup
+        .lock(
+            &first_user,
+            BASE_ASSET_TOKEN_ID,
+            USER_BALANCE / 2,
+            LOCK_OPTIONS[1],
+        )
+        .assert_user_error("Invalid lock choice");
+    setup
+        .b_mock
+        .execute_tx(&owner, &setup.sc_wrapper, &rust_biguint!(0), |sc| {
+            sc.cancel_scheduled_lock_options();
+        })
+        .assert_user_error("No scheduled lock options change");
+
+    // modifying the options moves the scheduled ones to the current options
+    setup
+        .b_mock
+        .execute_tx(&owner, &setup.sc_wrapper, &rust_biguint!(0), |sc| {
+            sc.update_lock_option_penalty(EPOCHS_IN_YEAR, 1_000);
+        })
+        .assert_ok();
+    setup
+        .b_mock
+        .execute_query(&setup.sc_wrapper, |sc| {
+            assert!(sc.scheduled_lock_options().is_empty());
+            assert!(sc.scheduled_lock_options_epoch().is_empty());
+
+            let lock_options = sc.current_lock_options();
+            assert_eq!(lock_options.len(), 2);
+            assert_eq!(lock_options.get(1).penalty_start_percentage, 1_000);
+            assert_eq!(lock_options.get(2).lock_epochs, 4 * EPOCHS_IN_YEAR);
+
+            // the multiplier and availability window of the removed option are cleared
+            assert_eq!(
+                sc.lock_option_energy_multiplier(LOCK_OPTIONS[0]).get(),
+                12_000
+            );
+            assert!(sc.lock_option_energy_multiplier(LOCK_OPTIONS[1]).is_empty());
+            assert_eq!(
+                sc.lock_option_availability(LOCK_OPTIONS[0])
+                    .get()
+                    .available_from_epoch,
+                Some(5)
+            );
+            assert!(sc.lock_option_availability(LOCK_OPTIONS[1]).is_empty());
+        })
+        .assert_ok();
+}
+
+#[test]
+fn lock_options_schedule_delay_test() {
+    let mut setup = SimpleLockEnergySetup::new(energy_factory::contract_obj);
+    let owner = setup.owner.clone();
+
+    setup
+        .b_mock
+        .execute_query(&setup.sc_wrapper, |sc| {
+            assert_eq!(
+                sc.get_lock_options_schedule_delay(),
+                MIN_LOCK_OPTIONS_SCHEDULE_DELAY
+            );
+        })
+        .assert_ok();
+    setup
+        .b_mock
+        .execute_tx(&owner, &setup.sc_wrapper, &rust_biguint!(0), |sc| {
+            sc.set_lock_options_schedule_delay(MIN_LOCK_OPTIONS_SCHEDULE_DELAY - 1);
+        })
+        .assert_user_error("Invalid schedule delay");
+    setup
+        .b_mock
+        .execute_tx(&owner, &setup.sc_wrapper, &rust_biguint!(0), |sc| {
+            sc.set_lock_options_schedule_delay(30);
+        })
+        .assert_ok();
+
+    setup.b_mock.set_block_epoch(10);
+    setup
+        .b_mock
+        .execute_tx(&owner, &setup.sc_wrapper, &rust_biguint!(0), |sc| {
+            let mut lock_options = MultiValueEncoded::new();
+            lock_options.push((EPOCHS_IN_YEAR, 2_000).into());
+            sc.schedule_lock_options(39, lock_options);
+        })
+        .assert_user_error("Effective epoch too early");
+    setup
+        .b_mock
+        .execute_tx(&owner, &setup.sc_wrapper, &rust_biguint!(0), |sc| {
+            let mut lock_options = MultiValueEncoded::new();
+            lock_options.push((EPOCHS_IN_YEAR, 2_000).into());
+            sc.schedule_lock_options(40, lock_options);
+        })
+        .assert_ok();
+}
@@ -0,0 +1,448 @@
+#![allow(deprecated)]
+
+mod energy_factory_setup;
+
+use energy_factory::{
+    lock_options_endpoints::LockOptionsEndpointsModule, penalty::PENALTY_PRECISION,
+    penalty_accounting::PenaltyAccountingModule, unlock_with_penalty::UnlockWithPenaltyModule,
+};
+use energy_factory_setup::*;
+use multiversx_sc_modules::pause::PauseModule;
+use simple_lock::locked_token::LockedTokenAttributes;
+
+use multiversx_sc_scenario::{
+    managed_address, managed_biguint, managed_token_id_wrapped, rust_biguint,
+    whitebox_legacy::TxTokenTransfer, DebugApi,
+};
+
+#[test]
+fn lock_reduce_quote_test() {
+    let mut setup = SimpleLockEnergySetup::new(energy_factory::contract_obj);
+    let first_user = setup.first_user.clone();
+    let quarter_balance = USER_BALANCE / 4;
+
+    // unlocks at epoch 1_440
+    setup.b_mock.set_block_epoch(5);
+    setup
+        .lock(
+            &first_user,
+            BASE_ASSET_TOKEN_ID,
+            USER_BALANCE / 2,
+            LOCK_OPTIONS[2],
+        )
+        .assert_ok();
+
+    let mut reduce_quote_amounts = (rust_biguint!(0), rust_biguint!(0), 0);
+    let mut reduce_energy_delta = num_bigint::BigInt::default();
+    setup
+        .b_mock
+        .execute_query(&setup.sc_wrapper, |sc| {
+            let quote = sc.get_reduce_lock_period_quote(
+                managed_address!(&first_user),
+                1,
+                managed_biguint!(quarter_balance),
+                LOCK_OPTIONS[0],
+            );
+            reduce_quote_amounts = (
+                to_rust_biguint(quote.penalty_amount),
+                to_rust_biguint(quote.received_amount),
+                quote.new_unlock_epoch,
+            );
+            reduce_energy_delta = num_bigint::BigInt::from_signed_bytes_be(
+                quote.energy_delta.to_signed_bytes_be().as_slice(),
+            );
+        })
+        .assert_ok();
+
+    let (penalty_amount, received_amount, new_unlock_epoch) = reduce_quote_amounts;
+    assert_eq!(new_unlock_epoch, 360);
+    assert_eq!(
+        penalty_amount,
+        setup.get_penalty_amount(quarter_balance, 1_440 - 5, 360 - 5)
+    );
+    assert_eq!(
+        &penalty_amount + &received_amount,
+        rust_biguint!(quarter_balance)
+    );
+
+    let energy_before = setup.get_user_energy(&first_user);
+    setup
+        .reduce_lock_period(&first_user, 1, quarter_balance, LOCK_OPTIONS[0])
+        .assert_ok();
+    let energy_after = setup.get_user_energy(&first_user);
+    assert_eq!(
+        num_bigint::BigInt::from(energy_after.clone()) - num_bigint::BigInt::from(energy_before),
+        reduce_energy_delta
+    );
+    setup
+        .b_mock
+        .check_nft_balance::<LockedTokenAttributes<DebugApi>>(
+            &first_user,
+            LOCKED_TOKEN_ID,
+            2,
+            &received_amount,
+            None,
+        );
+
+    // unlock early quote for the rest of the first position
+    let mut unlock_quote_amounts = (rust_biguint!(0), 0);
+    setup
+        .b_mock
+        .execute_query(&setup.sc_wrapper, |sc| {
+            let quote = sc.get_unlock_early_quote(
+                managed_address!(&first_user),
+                1,
+                managed_biguint!(quarter_balance),
+            );
+            unlock_quote_amounts = (
+                to_rust_biguint(quote.received_amount),
+                quote.new_unlock_epoch,
+            );
+            assert!(quote.energy_delta < 0);
+        })
+        .assert_ok();
+    assert_eq!(unlock_quote_amounts.1, 5);
+    assert_eq!(setup.get_user_energy(&first_user), energy_after);
+
+    setup
+        .unlock_early(&first_user, 1, quarter_balance)
+        .assert_ok();
+    setup.b_mock.check_esdt_balance(
+        &first_user,
+        BASE_ASSET_TOKEN_ID,
+        &(rust_biguint!(USER_BALANCE / 2) + &unlock_quote_amounts.0),
+    );
+
+    // quotes are still available while paused
+    setup
+        .b_mock
+        .execute_tx(&setup.owner, &setup.sc_wrapper, &rust_biguint!(0), |sc| {
+            sc.set_paused(true);
+        })
+        .assert_ok();
+    setup
+        .b_mock
+        .execute_query(&setup.sc_wrapper, |sc| {
+            let quote = sc.get_unlock_early_quote(
+                managed_address!(&first_user),
+                2,
+                managed_biguint!(1_000u64),
+            );
+            assert!(quote.penalty_amount > 0);
+        })
+        .assert_ok();
+}
+
+#[test]
+fn chained_lock_reduction_precision_test() {
+    let mut setup = SimpleLockEnergySetup::new(energy_factory::contract_obj);
+    let amount = USER_BALANCE / 3;
+
+    let direct_unlock_amount =
+        rust_biguint!(amount) - setup.get_penalty_amount(amount, LOCK_OPTIONS[2], 0);
+
+    // 4 years -> 2 years -> 1 year -> unlocked
+    let mut chained_unlock_amount = rust_biguint!(amount);
+    for (prev_lock_epochs, new_lock_epochs) in [
+        (LOCK_OPTIONS[2], LOCK_OPTIONS[1]),
+        (LOCK_OPTIONS[1], LOCK_OPTIONS[0]),
+        (LOCK_OPTIONS[0], 0),
+    ] {
+        let current_amount = u64::try_from(&chained_unlock_amount).unwrap();
+        chained_unlock_amount -=
+            setup.get_penalty_amount(current_amount, prev_lock_epochs, new_lock_epochs);
+    }
+
+    // the results only differ by rounding dust, relative to PENALTY_PRECISION
+    let difference = if chained_unlock_amount > direct_unlock_amount {
+        chained_unlock_amount - direct_unlock_amount
+    } else {
+        direct_unlock_amount - chained_unlock_amount
+    };
+    assert!(difference * PENALTY_PRECISION <= rust_biguint!(amount));
+}
+
+#[test]
+fn batch_lock_reduction_test() {
+    let mut setup = SimpleLockEnergySetup::new(energy_factory::contract_obj);
+    let first_user = setup.first_user.clone();
+    let half_balance = USER_BALANCE / 2;
+    let quarter_balance = USER_BALANCE / 4;
+    let eighth_balance = USER_BALANCE / 8;
+
+    setup.b_mock.set_block_epoch(0);
+
+    setup
+        .lock(
+            &first_user,
+            BASE_ASSET_TOKEN_ID,
+            quarter_balance,
+            LOCK_OPTIONS[1],
+        )
+        .assert_ok();
+    setup
+        .lock(
+            &first_user,
+            BASE_ASSET_TOKEN_ID,
+            quarter_balance,
+            LOCK_OPTIONS[2],
+        )
+        .assert_ok();
+
+    // reduce half of both positions into a single one
+    let first_reduce_penalty =
+        setup.get_penalty_amount(eighth_balance, LOCK_OPTIONS[1], LOCK_OPTIONS[0]);
+    let second_reduce_penalty =
+        setup.get_penalty_amount(eighth_balance, LOCK_OPTIONS[2], LOCK_OPTIONS[0]);
+    let payments = [
+        TxTokenTransfer {
+            token_identifier: LOCKED_TOKEN_ID.to_vec(),
+            nonce: 1,
+            value: rust_biguint!(eighth_balance),
+        },
+        TxTokenTransfer {
+            token_identifier: LOCKED_TOKEN_ID.to_vec(),
+            nonce: 2,
+            value: rust_biguint!(eighth_balance),
+        },
+    ];
+    setup
+        .b_mock
+        .execute_esdt_multi_transfer(&first_user, &setup.sc_wrapper, &payments[..], |sc| {
+            sc.reduce_lock_period(LOCK_OPTIONS[0]);
+        })
+        .assert_ok();
+
+    let expected_reduced_amount =
+        rust_biguint!(eighth_balance) * 2u64 - &first_reduce_penalty - &second_reduce_penalty;
+    setup.b_mock.check_nft_balance(
+        &first_user,
+        LOCKED_TOKEN_ID,
+        3,
+        &expected_reduced_amount,
+        Some(&LockedTokenAttributes::<DebugApi> {
+            original_token_id: managed_token_id_wrapped!(BASE_ASSET_TOKEN_ID),
+            original_token_nonce: 0,
+            unlock_epoch: EPOCHS_IN_YEAR,
+        }),
+    );
+    setup
+        .b_mock
+        .execute_query(&setup.sc_wrapper, |sc| {
+            assert_eq!(
+                to_rust_biguint(sc.total_penalties_collected().get()),
+                &first_reduce_penalty + &second_reduce_penalty
+            );
+        })
+        .assert_ok();
+
+    // unlock the rest of both positions in a single deposit
+    let first_unlock_penalty = setup.get_penalty_amount(eighth_balance, LOCK_OPTIONS[1], 0);
+    let second_unlock_penalty = setup.get_penalty_amount(eighth_balance, LOCK_OPTIONS[2], 0);
+    setup
+        .b_mock
+        .execute_esdt_multi_transfer(&first_user, &setup.sc_wrapper, &payments[..], |sc| {
+            sc.unlock_early();
+        })
+        .assert_ok();
+
+    let expected_unlocked_amount =
+        rust_biguint!(eighth_balance) * 2u64 - &first_unlock_penalty - &second_unlock_penalty;
+    setup.b_mock.check_esdt_balance(
+        &first_user,
+        BASE_ASSET_TOKEN_ID,
+        &(expected_unlocked_amount + half_balance),
+    );
+
+    // only the merged position is left
+    let user_energy = setup.get_user_energy(&first_user);
+    assert_eq!(user_energy, expected_reduced_amount * EPOCHS_IN_YEAR);
+
+    // all payments must be LOCKED tokens
+    let invalid_payments = [
+        TxTokenTransfer {
+            token_identifier: LOCKED_TOKEN_ID.to_vec(),
+            nonce: 3,
+            value: rust_biguint!(1_000u64),
+        },
+        TxTokenTransfer {
+            token_identifier: BASE_ASSET_TOKEN_ID.to_vec(),
+            nonce: 0,
+            value: rust_biguint!(1_000u64),
+        },
+    ];
+    setup
+        .b_mock
+        .execute_esdt_multi_transfer(
+            &first_user,
+            &setup.sc_wrapper,
+            &invalid_payments[..],
+            |sc| {
+                sc.unlock_early();
+            },
+        )
+        .assert_error(4, "Invalid payment token");
+}
+
+#[test]
+fn reduce_lock_period_to_epoch_test() {
+    let mut setup = SimpleLockEnergySetup::new(energy_factory::contract_obj);
+    let first_user = setup.first_user.clone();
+    let half_balance = USER_BALANCE / 2;
+
+    setup.b_mock.set_block_epoch(0);
+    setup
+        .lock(
+            &first_user,
+            BASE_ASSET_TOKEN_ID,
+            half_balance,
+            LOCK_OPTIONS[1],
+        )
+        .assert_ok();
+
+    let current_epoch = 10;
+    setup.b_mock.set_block_epoch(current_epoch);
+
+    let penalty_amount = setup.get_penalty_amount(
+        half_balance,
+        LOCK_OPTIONS[1] - current_epoch,
+        EPOCHS_IN_YEAR - current_epoch,
+    );
+
+    // target must be a month boundary, and not after the current unlock epoch
+    setup
+        .reduce_lock_period_to_epoch(
+            &first_user,
+            1,
+            half_balance,
+            EPOCHS_IN_YEAR + 5,
+            &penalty_amount,
+        )
+        .assert_error(
+            4,
+            "Target unlock epoch not aligned to the unlock epoch granularity",
+        );
+    setup
+        .reduce_lock_period_to_epoch(
+            &first_user,
+            1,
+            half_balance,
+            LOCK_OPTIONS[1] + 30,
+            &penalty_amount,
+        )
+        .assert_error(4, "Invalid lock choice");
+
+    // target must be reachable through one of the lock options
+    setup
+        .reduce_lock_period_to_epoch(&first_user, 1, half_balance, 60, &penalty_amount)
+        .assert_error(4, "Invalid lock choice");
+
+    // slippage protection
+    setup
+        .reduce_lock_period_to_epoch(
+            &first_user,
+            1,
+            half_balance,
+            EPOCHS_IN_YEAR,
+            &(&penalty_amount - 1u64),
+        )
+        .assert_error(4, "Penalty above the maximum");
+
+    setup
+        .reduce_lock_period_to_epoch(
+            &first_user,
+            1,
+            half_balance,
+            EPOCHS_IN_YEAR,
+            &penalty_amount,
+        )
+        .assert_ok();
+
+    let expected_locked_token_balance = rust_biguint!(half_balance) - &penalty_amount;
+    setup.b_mock.check_nft_balance(
+        &first_user,
+        LOCKED_TOKEN_ID,
+        2,
+        &expected_locked_token_balance,
+        Some(&LockedTokenAttributes::<DebugApi> {
+            original_token_id: managed_token_id_wrapped!(BASE_ASSET_TOKEN_ID),
+            original_token_nonce: 0,
+            unlock_epoch: EPOCHS_IN_YEAR,
+        }),
+    );
+
+    let user_energy = setup.get_user_energy(&first_user);
+    assert_eq!(
+        user_energy,
+        expected_locked_token_balance * (EPOCHS_IN_YEAR - current_epoch)
+    );
+
+    // the new unlock epoch must be before the current one
+    setup
+        .reduce_lock_period_to_epoch(&first_user, 2, 1_000, EPOCHS_IN_YEAR, &rust_biguint!(0))
+        .assert_error(4, "Invalid reduce choice");
+    setup
+        .reduce_lock_period(&first_user, 2, 1_000, LOCK_OPTIONS[0])
+        .assert_error(4, "Invalid reduce choice");
+}
+
+#[test]
+fn reduce_lock_period_to_epoch_range_mode_test() {
+    let mut setup = SimpleLockEnergySetup::new(energy_factory::contract_obj);
+    let first_user = setup.first_user.clone();
+    let half_balance = USER_BALANCE / 2;
+
+    setup.b_mock.set_block_epoch(0);
+    setup
+        .lock(
+            &first_user,
+            BASE_ASSET_TOKEN_ID,
+            half_balance,
+            LOCK_OPTIONS[1],
+        )
+        .assert_ok();
+    setup
+        .b_mock
+        .execute_tx(&setup.owner, &setup.sc_wrapper, &rust_biguint!(0), |sc| {
+            sc.set_lock_range_mode(true);
+        })
+        .assert_ok();
+
+    let current_epoch = 10;
+    setup.b_mock.set_block_epoch(current_epoch);
+
+    let target_unlock_epoch = 480;
+    let penalty_amount = setup.get_penalty_amount(
+        half_balance,
+        LOCK_OPTIONS[1] - current_epoch,
+        target_unlock_epoch - current_epoch,
+    );
+
+    // below the shortest option
+    setup
+        .reduce_lock_period_to_epoch(&first_user, 1, half_balance, 330, &penalty_amount)
+        .assert_error(4, "Invalid lock choice");
+
+    setup
+        .reduce_lock_period_to_epoch(
+            &first_user,
+            1,
+            half_balance,
+            target_unlock_epoch,
+            &penalty_amount,
+        )
+        .assert_ok();
+
+    let expected_locked_token_balance = rust_biguint!(half_balance) - &penalty_amount;
+    setup.b_mock.check_nft_balance(
+        &first_user,
+        LOCKED_TOKEN_ID,
+        2,
+        &expected_locked_token_balance,
+        Some(&LockedTokenAttributes::<DebugApi> {
+            original_token_id: managed_token_id_wrapped!(BASE_ASSET_TOKEN_ID),
+            original_token_nonce: 0,
+            unlock_epoch: target_unlock_epoch,
+        }),
+    );
+}
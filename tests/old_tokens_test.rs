@@ -8,6 +8,7 @@ use common_structs::{
 };
 use energy_factory::{
     energy::{Energy, EnergyModule},
+    lock_options::LockOptionsModule,
     migration::SimpleLockMigrationModule,
 };
 use energy_factory_setup::*;
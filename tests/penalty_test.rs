@@ -0,0 +1,552 @@
+#![allow(deprecated)]
+
+mod energy_factory_setup;
+
+use energy_factory::{
+    lock_options_endpoints::LockOptionsEndpointsModule,
+    penalty::{to_penalty_precision, LocalPenaltyModule, PenaltyCurve, PENALTY_PRECISION},
+    penalty_accounting::PenaltyAccountingModule,
+    penalty_discount::PenaltyDiscountModule,
+    penalty_distribution::{PenaltyDestination, PenaltyDistributionModule},
+    unbond::{UnbondMode, UnbondModule},
+    unlock_with_penalty::UnlockWithPenaltyModule,
+};
+use energy_factory_setup::{fees_collector_mock::FeesCollectorMock, *};
+use multiversx_sc::{
+    codec::multi_types::OptionalValue,
+    types::{BigUint, MultiValueEncoded},
+};
+use simple_lock::locked_token::LockedTokenAttributes;
+
+use multiversx_sc_scenario::{managed_address, managed_biguint, rust_biguint, DebugApi};
+
+#[test]
+fn penalty_curve_test() {
+    let mut setup = SimpleLockEnergySetup::new(energy_factory::contract_obj);
+    let owner = setup.owner.clone();
+    let midpoint = (LOCK_OPTIONS[0] + LOCK_OPTIONS[1]) / 2;
+
+    // penalties at the midpoint between the first two options
+    let expected_midpoint_penalties = [
+        (PenaltyCurve::Linear, 5_000),
+        (PenaltyCurve::Quadratic, 4_500),
+        (PenaltyCurve::Exponential, 5_600),
+    ];
+    for (penalty_curve, expected_midpoint_penalty) in expected_midpoint_penalties {
+        setup
+            .b_mock
+            .execute_tx(&owner, &setup.sc_wrapper, &rust_biguint!(0), |sc| {
+                sc.set_penalty_curve(penalty_curve);
+            })
+            .assert_ok();
+
+        setup
+            .b_mock
+            .execute_query(&setup.sc_wrapper, |sc| {
+                assert_eq!(
+                    sc.calculate_penalty_percentage_full_unlock(midpoint),
+                    to_penalty_precision(expected_midpoint_penalty)
+                );
+
+                // curves go through every option
+                for (option, penalty) in LOCK_OPTIONS.iter().zip(PENALTY_PERCENTAGES.iter()) {
+                    assert_eq!(
+                        sc.calculate_penalty_percentage_full_unlock(*option),
+                        to_penalty_precision(*penalty)
+                    );
+                }
+
+                let mut prev_penalty = 0;
+                for lock_epochs in 1..=LOCK_OPTIONS[2] {
+                    let penalty = sc.calculate_penalty_percentage_full_unlock(lock_epochs);
+                    assert!(penalty >= prev_penalty);
+                    prev_penalty = penalty;
+
+                    let partial_penalty = sc
+                        .calculate_penalty_percentage_partial_unlock(lock_epochs, LOCK_OPTIONS[0]);
+                    assert!(partial_penalty <= PENALTY_PRECISION);
+                }
+
+                let mut prev_partial_penalty = 0;
+                for lock_epochs in LOCK_OPTIONS[0]..=LOCK_OPTIONS[2] {
+                    let partial_penalty = sc
+                        .calculate_penalty_percentage_partial_unlock(lock_epochs, LOCK_OPTIONS[0]);
+                    assert!(partial_penalty >= prev_partial_penalty);
+                    prev_partial_penalty = partial_penalty;
+                }
+            })
+            .assert_ok();
+    }
+}
+
+#[test]
+fn penalty_distribution_test() {
+    let mut setup = SimpleLockEnergySetup::new(energy_factory::contract_obj);
+    let owner = setup.owner.clone();
+    let first_user = setup.first_user.clone();
+    let half_balance = USER_BALANCE / 2;
+
+    let fees_collector = setup
+        .b_mock
+        .create_sc_account(
+            &rust_biguint!(0),
+            Some(&owner),
+            FeesCollectorMock::new,
+            "fees collector",
+        )
+        .address_ref()
+        .clone();
+    let treasury = setup.b_mock.create_user_account(&rust_biguint!(0));
+
+    setup
+        .b_mock
+        .execute_tx(&owner, &setup.sc_wrapper, &rust_biguint!(0), |sc| {
+            sc.set_penalty_distribution(5_000, 3_000, 2_000);
+        })
+        .assert_user_error("Fees collector address not set");
+    setup
+        .b_mock
+        .execute_tx(&owner, &setup.sc_wrapper, &rust_biguint!(0), |sc| {
+            sc.set_fees_collector_address(managed_address!(&fees_collector));
+            sc.set_treasury_address(managed_address!(&treasury));
+        })
+        .assert_ok();
+    setup
+        .b_mock
+        .execute_tx(&owner, &setup.sc_wrapper, &rust_biguint!(0), |sc| {
+            sc.set_penalty_distribution(5_000, 3_000, 1_000);
+        })
+        .assert_user_error("Invalid penalty distribution");
+    setup
+        .b_mock
+        .execute_tx(&owner, &setup.sc_wrapper, &rust_biguint!(0), |sc| {
+            sc.set_penalty_distribution(5_000, 3_000, 2_000);
+        })
+        .assert_ok();
+
+    setup.b_mock.set_block_epoch(0);
+    setup
+        .lock(
+            &first_user,
+            BASE_ASSET_TOKEN_ID,
+            half_balance,
+            LOCK_OPTIONS[0],
+        )
+        .assert_ok();
+
+    let penalty_amount = setup.get_penalty_amount(half_balance, LOCK_OPTIONS[0], 0);
+    setup.unlock_early(&first_user, 1, half_balance).assert_ok();
+
+    setup.b_mock.check_esdt_balance(
+        &first_user,
+        BASE_ASSET_TOKEN_ID,
+        &(rust_biguint!(USER_BALANCE) - &penalty_amount),
+    );
+    let fees_collector_amount = &penalty_amount * 3_000u64 / 10_000u64;
+    let treasury_amount = &penalty_amount * 2_000u64 / 10_000u64;
+    let burn_amount = &penalty_amount - &fees_collector_amount - &treasury_amount;
+    setup
+        .b_mock
+        .check_esdt_balance(&fees_collector, BASE_ASSET_TOKEN_ID, &fees_collector_amount);
+    setup
+        .b_mock
+        .check_esdt_balance(&treasury, BASE_ASSET_TOKEN_ID, &treasury_amount);
+
+    // the unstake SC gets nothing from the penalty
+    setup
+        .b_mock
+        .check_nft_balance::<LockedTokenAttributes<DebugApi>>(
+            &setup.unbond_sc_mock,
+            LOCKED_TOKEN_ID,
+            1,
+            &rust_biguint!(0),
+            None,
+        );
+
+    setup
+        .b_mock
+        .execute_query(&setup.sc_wrapper, |sc| {
+            assert_eq!(
+                to_rust_biguint(sc.total_penalty_distributed(PenaltyDestination::Burn).get()),
+                burn_amount
+            );
+            assert_eq!(
+                to_rust_biguint(
+                    sc.total_penalty_distributed(PenaltyDestination::FeesCollector)
+                        .get()
+                ),
+                fees_collector_amount
+            );
+            assert_eq!(
+                to_rust_biguint(
+                    sc.total_penalty_distributed(PenaltyDestination::Treasury)
+                        .get()
+                ),
+                treasury_amount
+            );
+        })
+        .assert_ok();
+
+    // reducing the lock period distributes the penalty as well
+    setup
+        .lock(
+            &first_user,
+            BASE_ASSET_TOKEN_ID,
+            half_balance,
+            LOCK_OPTIONS[2],
+        )
+        .assert_ok();
+    let reduce_penalty_amount =
+        setup.get_penalty_amount(half_balance, LOCK_OPTIONS[2], LOCK_OPTIONS[0]);
+    setup
+        .reduce_lock_period(&first_user, 2, half_balance, LOCK_OPTIONS[0])
+        .assert_ok();
+    setup.b_mock.check_esdt_balance(
+        &treasury,
+        BASE_ASSET_TOKEN_ID,
+        &(treasury_amount + &reduce_penalty_amount * 2_000u64 / 10_000u64),
+    );
+}
+
+#[test]
+fn penalty_discount_test() {
+    let mut setup = SimpleLockEnergySetup::new(energy_factory::contract_obj);
+    let owner = setup.owner.clone();
+    let first_user = setup.first_user.clone();
+    let half_balance = USER_BALANCE / 2;
+
+    setup.b_mock.set_block_epoch(0);
+    setup
+        .lock(
+            &first_user,
+            BASE_ASSET_TOKEN_ID,
+            half_balance,
+            LOCK_OPTIONS[1],
+        )
+        .assert_ok();
+    let kept_energy = setup.get_user_energy(&first_user);
+    setup
+        .lock(
+            &first_user,
+            BASE_ASSET_TOKEN_ID,
+            half_balance,
+            LOCK_OPTIONS[0],
+        )
+        .assert_ok();
+
+    let energy_thresholds = [
+        &kept_energy / 2u64,
+        kept_energy.clone(),
+        &kept_energy * 2u64,
+    ];
+
+    setup
+        .b_mock
+        .execute_tx(&owner, &setup.sc_wrapper, &rust_biguint!(0), |sc| {
+            let mut tiers = MultiValueEncoded::new();
+            tiers.push(
+                (
+                    BigUint::from_bytes_be(&energy_thresholds[1].to_bytes_be()),
+                    2_500,
+                )
+                    .into(),
+            );
+            tiers.push(
+                (
+                    BigUint::from_bytes_be(&energy_thresholds[0].to_bytes_be()),
+                    4_000,
+                )
+                    .into(),
+            );
+            sc.set_penalty_discount_tiers(tiers);
+        })
+        .assert_user_error("Penalty discount tiers must be increasing");
+    setup
+        .b_mock
+        .execute_tx(&owner, &setup.sc_wrapper, &rust_biguint!(0), |sc| {
+            let mut tiers = MultiValueEncoded::new();
+            tiers.push((managed_biguint!(1), 5_001).into());
+            sc.set_penalty_discount_tiers(tiers);
+        })
+        .assert_user_error("Invalid penalty discount");
+    setup
+        .b_mock
+        .execute_tx(&owner, &setup.sc_wrapper, &rust_biguint!(0), |sc| {
+            let mut tiers = MultiValueEncoded::new();
+            for (threshold, discount) in energy_thresholds.iter().zip([1_000, 2_500, 5_000]) {
+                tiers.push((BigUint::from_bytes_be(&threshold.to_bytes_be()), discount).into());
+            }
+            sc.set_penalty_discount_tiers(tiers);
+        })
+        .assert_ok();
+
+    // the energy left after unlocking the second position is exactly the second tier's
+    let full_penalty_amount = setup.get_penalty_amount(half_balance, LOCK_OPTIONS[0], 0);
+    let discounted_penalty_amount = &full_penalty_amount * 7_500u64 / 10_000u64;
+    setup
+        .b_mock
+        .execute_query(&setup.sc_wrapper, |sc| {
+            assert_eq!(
+                sc.get_penalty_discount_for_user(managed_address!(&first_user)),
+                2_500
+            );
+            assert_eq!(
+                sc.get_penalty_discount_for_user(managed_address!(&setup.second_user)),
+                0
+            );
+
+            let penalty_amount = sc.calculate_penalty_amount(
+                &managed_biguint!(half_balance),
+                LOCK_OPTIONS[0],
+                0,
+                OptionalValue::Some(managed_address!(&first_user)),
+            );
+            assert_eq!(to_rust_biguint(penalty_amount), discounted_penalty_amount);
+        })
+        .assert_ok();
+
+    setup.unlock_early(&first_user, 2, half_balance).assert_ok();
+    let mut expected_balance = rust_biguint!(half_balance) - &discounted_penalty_amount;
+    setup
+        .b_mock
+        .check_esdt_balance(&first_user, BASE_ASSET_TOKEN_ID, &expected_balance);
+
+    // the position being unlocked does not count towards the discount
+    let full_penalty_amount = setup.get_penalty_amount(half_balance, LOCK_OPTIONS[1], 0);
+    setup.unlock_early(&first_user, 1, half_balance).assert_ok();
+    expected_balance += rust_biguint!(half_balance) - &full_penalty_amount;
+    setup
+        .b_mock
+        .check_esdt_balance(&first_user, BASE_ASSET_TOKEN_ID, &expected_balance);
+
+    // clearing the tiers removes the discount
+    setup
+        .b_mock
+        .execute_tx(&owner, &setup.sc_wrapper, &rust_biguint!(0), |sc| {
+            sc.set_penalty_discount_tiers(MultiValueEncoded::new());
+        })
+        .assert_ok();
+    setup
+        .b_mock
+        .execute_query(&setup.sc_wrapper, |sc| {
+            assert!(sc.penalty_discount_tiers().get().is_empty());
+        })
+        .assert_ok();
+}
+
+#[test]
+fn penalty_discount_with_energy_multiplier_test() {
+    let mut setup = SimpleLockEnergySetup::new(energy_factory::contract_obj);
+    let owner = setup.owner.clone();
+    let first_user = setup.first_user.clone();
+    let half_balance = USER_BALANCE / 2;
+
+    setup
+        .b_mock
+        .execute_tx(&owner, &setup.sc_wrapper, &rust_biguint!(0), |sc| {
+            sc.set_lock_option_energy_multiplier(LOCK_OPTIONS[1], 20_000);
+        })
+        .assert_ok();
+
+    setup.b_mock.set_block_epoch(0);
+    setup
+        .lock(
+            &first_user,
+            BASE_ASSET_TOKEN_ID,
+            half_balance,
+            LOCK_OPTIONS[0],
+        )
+        .assert_ok();
+    setup
+        .lock(
+            &first_user,
+            BASE_ASSET_TOKEN_ID,
+            half_balance,
+            LOCK_OPTIONS[1],
+        )
+        .assert_ok();
+
+    // the energy left after unlocking the second position is below the threshold,
+    // as its tokens have twice the weight
+    let kept_energy = rust_biguint!(half_balance) * LOCK_OPTIONS[0];
+    let unweighted_kept_energy =
+        setup.get_user_energy(&first_user) - rust_biguint!(half_balance) * LOCK_OPTIONS[1];
+    assert!(unweighted_kept_energy > &kept_energy * 2u64);
+    setup
+        .b_mock
+        .execute_tx(&owner, &setup.sc_wrapper, &rust_biguint!(0), |sc| {
+            let mut tiers = MultiValueEncoded::new();
+            let threshold = &kept_energy * 2u64;
+            tiers.push((BigUint::from_bytes_be(&threshold.to_bytes_be()), 5_000).into());
+            sc.set_penalty_discount_tiers(tiers);
+        })
+        .assert_ok();
+
+    let full_penalty_amount = setup.get_penalty_amount(half_balance, LOCK_OPTIONS[1], 0);
+    setup
+        .b_mock
+        .execute_query(&setup.sc_wrapper, |sc| {
+            let penalty_amount = sc.calculate_penalty_amount(
+                &managed_biguint!(half_balance),
+                LOCK_OPTIONS[1],
+                0,
+                OptionalValue::Some(managed_address!(&first_user)),
+            );
+            assert_eq!(to_rust_biguint(penalty_amount), full_penalty_amount);
+        })
+        .assert_ok();
+
+    setup.unlock_early(&first_user, 2, half_balance).assert_ok();
+    setup.b_mock.check_esdt_balance(
+        &first_user,
+        BASE_ASSET_TOKEN_ID,
+        &(rust_biguint!(half_balance) - &full_penalty_amount),
+    );
+}
+
+#[test]
+fn penalty_accounting_test() {
+    let mut setup = SimpleLockEnergySetup::new(energy_factory::contract_obj);
+    let first_user = setup.first_user.clone();
+    let quarter_balance = USER_BALANCE / 4;
+
+    // early unlock penalties are only final if distributed here
+    setup
+        .b_mock
+        .execute_tx(&setup.owner, &setup.sc_wrapper, &rust_biguint!(0), |sc| {
+            sc.set_penalty_distribution(10_000, 0, 0);
+        })
+        .assert_ok();
+
+    // unlocks at epoch 720
+    setup.b_mock.set_block_epoch(5);
+    setup
+        .lock(
+            &first_user,
+            BASE_ASSET_TOKEN_ID,
+            USER_BALANCE / 2,
+            LOCK_OPTIONS[1],
+        )
+        .assert_ok();
+
+    // week 0
+    setup.b_mock.set_block_epoch(6);
+    let unlock_penalty = setup.get_penalty_amount(quarter_balance, 720 - 6, 0);
+    setup
+        .unlock_early(&first_user, 1, quarter_balance)
+        .assert_ok();
+
+    // week 1, reduced to unlock at epoch 360
+    setup.b_mock.set_block_epoch(8);
+    let reduce_penalty = setup.get_penalty_amount(quarter_balance, 720 - 8, 360 - 8);
+    setup
+        .reduce_lock_period(&first_user, 1, quarter_balance, LOCK_OPTIONS[0])
+        .assert_ok();
+
+    let total_penalty = &unlock_penalty + &reduce_penalty;
+    setup
+        .b_mock
+        .execute_query(&setup.sc_wrapper, |sc| {
+            assert_eq!(
+                to_rust_biguint(sc.total_penalties_collected().get()),
+                total_penalty
+            );
+            assert_eq!(
+                to_rust_biguint(sc.penalties_collected_for_epoch(6).get()),
+                unlock_penalty
+            );
+            assert_eq!(
+                to_rust_biguint(sc.penalties_collected_for_week(0).get()),
+                unlock_penalty
+            );
+            assert_eq!(
+                to_rust_biguint(sc.penalties_collected_for_week(1).get()),
+                reduce_penalty
+            );
+
+            assert_eq!(
+                to_rust_biguint(sc.get_penalties_collected(0, 13)),
+                total_penalty
+            );
+            assert_eq!(
+                to_rust_biguint(sc.get_penalties_collected(6, 7)),
+                unlock_penalty
+            );
+            assert_eq!(
+                to_rust_biguint(sc.get_penalties_collected(7, 30)),
+                reduce_penalty
+            );
+            assert_eq!(
+                to_rust_biguint(sc.get_penalties_collected(9, 30)),
+                rust_biguint!(0)
+            );
+        })
+        .assert_ok();
+    setup
+        .b_mock
+        .execute_query(&setup.sc_wrapper, |sc| {
+            let _ = sc.get_penalties_collected(8, 6);
+        })
+        .assert_user_error("Invalid epoch range");
+}
+
+#[test]
+fn penalty_accounting_internal_unbond_test() {
+    let mut setup = SimpleLockEnergySetup::new(energy_factory::contract_obj);
+    let first_user = setup.first_user.clone();
+    let half_balance = USER_BALANCE / 2;
+    let quarter_balance = USER_BALANCE / 4;
+
+    setup
+        .b_mock
+        .execute_tx(&setup.owner, &setup.sc_wrapper, &rust_biguint!(0), |sc| {
+            sc.set_unbond_mode(UnbondMode::Internal);
+            sc.set_unbond_epochs(10);
+        })
+        .assert_ok();
+
+    setup.b_mock.set_block_epoch(5);
+    setup
+        .lock(
+            &first_user,
+            BASE_ASSET_TOKEN_ID,
+            half_balance,
+            LOCK_OPTIONS[1],
+        )
+        .assert_ok();
+
+    // without a distribution, the penalty is burned right away, so cancelling does not give it back
+    setup.b_mock.set_block_epoch(6);
+    let penalty_amount = setup.get_penalty_amount(quarter_balance, 720 - 6, 0);
+    setup
+        .unlock_early(&first_user, 1, quarter_balance)
+        .assert_ok();
+    setup
+        .b_mock
+        .execute_tx(&first_user, &setup.sc_wrapper, &rust_biguint!(0), |sc| {
+            let _ = sc.cancel_unbond();
+        })
+        .assert_ok();
+    setup
+        .b_mock
+        .check_nft_balance::<LockedTokenAttributes<DebugApi>>(
+            &first_user,
+            LOCKED_TOKEN_ID,
+            1,
+            &(rust_biguint!(half_balance) - &penalty_amount),
+            None,
+        );
+    setup
+        .b_mock
+        .execute_query(&setup.sc_wrapper, |sc| {
+            assert_eq!(
+                to_rust_biguint(sc.total_penalties_collected().get()),
+                penalty_amount
+            );
+            assert_eq!(
+                to_rust_biguint(sc.total_penalty_distributed(PenaltyDestination::Burn).get()),
+                penalty_amount
+            );
+        })
+        .assert_ok();
+}
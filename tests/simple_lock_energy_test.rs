@@ -3,7 +3,8 @@
 mod energy_factory_setup;
 
 use energy_factory::{
-    energy::EnergyModule, locked_token_transfer::LockedTokenTransferModule, SimpleLockEnergy,
+    energy::EnergyModule, locked_token_transfer::LockedTokenTransferModule,
+    penalty::PENALTY_PRECISION, SimpleLockEnergy,
 };
 use energy_factory_setup::*;
 use multiversx_sc::types::BigUint;
@@ -264,8 +265,10 @@ fn reduce_lock_period_test() {
         )
         .assert_ok();
 
-    let penalty_percentage = 3_333u64; // (6_000 - 4_000) / (10_000 - 4_000) = 3_333
-    let expected_penalty_amount = rust_biguint!(half_balance) * penalty_percentage / 10_000u64;
+    // (6_000 - 4_000) / (10_000 - 4_000) = 1 / 3
+    let penalty_percentage = PENALTY_PRECISION / 3;
+    let expected_penalty_amount =
+        rust_biguint!(half_balance) * penalty_percentage / PENALTY_PRECISION;
     let penalty_amount = setup.get_penalty_amount(half_balance, LOCK_OPTIONS[1], LOCK_OPTIONS[0]);
     assert_eq!(penalty_amount, expected_penalty_amount);
 
@@ -328,11 +328,12 @@ fn test_specific_tokens_merge() {
                 4_050,
             );
 
-            sc.user_energy(&managed_address!(&user)).set(Energy::new(
+            let energy_entry = Energy::new(
                 BigInt::zero(),
                 2_695,
                 BigUint::from_bytes_be(&total_expected.clone().to_bytes_be()),
-            ));
+            );
+            sc.user_energy(&managed_address!(&user)).set(&energy_entry);
         })
         .assert_ok();
 
@@ -0,0 +1,178 @@
+#![allow(deprecated)]
+
+mod energy_factory_setup;
+
+use energy_factory::{
+    penalty_accounting::PenaltyAccountingModule,
+    penalty_distribution::{PenaltyDestination, PenaltyDistributionModule},
+    unbond::{UnbondMode, UnbondModule},
+};
+use energy_factory_setup::*;
+use simple_lock::locked_token::LockedTokenAttributes;
+
+use multiversx_sc_scenario::{managed_address, rust_biguint, DebugApi};
+
+#[test]
+fn internal_unbond_test() {
+    let mut setup = SimpleLockEnergySetup::new(energy_factory::contract_obj);
+    let first_user = setup.first_user.clone();
+    let half_balance = USER_BALANCE / 2;
+    let quarter_balance = USER_BALANCE / 4;
+    let unbond_epochs = 10;
+
+    setup
+        .b_mock
+        .execute_tx(&setup.owner, &setup.sc_wrapper, &rust_biguint!(0), |sc| {
+            sc.set_unbond_mode(UnbondMode::Internal);
+            sc.set_unbond_epochs(unbond_epochs);
+        })
+        .assert_ok();
+
+    setup.b_mock.set_block_epoch(0);
+    setup
+        .lock(
+            &first_user,
+            BASE_ASSET_TOKEN_ID,
+            half_balance,
+            LOCK_OPTIONS[0],
+        )
+        .assert_ok();
+    let sc_address = setup.sc_wrapper.address_ref().clone();
+    let sc_locked_balance = setup
+        .b_mock
+        .get_esdt_balance(&sc_address, LOCKED_TOKEN_ID, 1);
+
+    // tokens are kept by the SC instead of being sent to the unstake SC, without the penalty
+    let first_penalty_amount = setup.get_penalty_amount(quarter_balance, EPOCHS_IN_YEAR, 0);
+    setup
+        .unlock_early(&first_user, 1, quarter_balance)
+        .assert_ok();
+    setup
+        .b_mock
+        .check_nft_balance::<LockedTokenAttributes<DebugApi>>(
+            &sc_address,
+            LOCKED_TOKEN_ID,
+            1,
+            &(&sc_locked_balance + quarter_balance - &first_penalty_amount),
+            None,
+        );
+    setup.b_mock.check_esdt_balance(
+        &first_user,
+        BASE_ASSET_TOKEN_ID,
+        &rust_biguint!(half_balance),
+    );
+    assert_eq!(
+        setup.get_user_energy(&first_user),
+        rust_biguint!(quarter_balance) * EPOCHS_IN_YEAR
+    );
+
+    setup
+        .b_mock
+        .execute_tx(&first_user, &setup.sc_wrapper, &rust_biguint!(0), |sc| {
+            let _ = sc.claim_unbonded();
+        })
+        .assert_error(4, "Nothing to claim");
+
+    // cancelling restores the locked position and energy, except for the burned penalty
+    setup.b_mock.set_block_epoch(5);
+    setup
+        .b_mock
+        .execute_tx(&first_user, &setup.sc_wrapper, &rust_biguint!(0), |sc| {
+            let _ = sc.cancel_unbond();
+        })
+        .assert_ok();
+    setup
+        .b_mock
+        .check_nft_balance::<LockedTokenAttributes<DebugApi>>(
+            &first_user,
+            LOCKED_TOKEN_ID,
+            1,
+            &(rust_biguint!(half_balance) - &first_penalty_amount),
+            None,
+        );
+    assert_eq!(
+        setup.get_user_energy(&first_user),
+        (rust_biguint!(half_balance) - &first_penalty_amount) * (EPOCHS_IN_YEAR - 5)
+    );
+
+    setup
+        .b_mock
+        .execute_tx(&first_user, &setup.sc_wrapper, &rust_biguint!(0), |sc| {
+            let _ = sc.cancel_unbond();
+        })
+        .assert_error(4, "No pending unbond");
+
+    // the base asset can be claimed after the unbond period
+    let penalty_amount = setup.get_penalty_amount(quarter_balance, EPOCHS_IN_YEAR - 5, 0);
+    setup
+        .unlock_early(&first_user, 1, quarter_balance)
+        .assert_ok();
+
+    setup.b_mock.set_block_epoch(5 + unbond_epochs);
+    setup
+        .b_mock
+        .execute_tx(&first_user, &setup.sc_wrapper, &rust_biguint!(0), |sc| {
+            let _ = sc.claim_unbonded();
+            assert!(sc.unbond_entries(&managed_address!(&first_user)).is_empty());
+        })
+        .assert_ok();
+    setup.b_mock.check_esdt_balance(
+        &first_user,
+        BASE_ASSET_TOKEN_ID,
+        &(rust_biguint!(half_balance + quarter_balance) - penalty_amount),
+    );
+    setup
+        .b_mock
+        .check_nft_balance::<LockedTokenAttributes<DebugApi>>(
+            &sc_address,
+            LOCKED_TOKEN_ID,
+            1,
+            &sc_locked_balance,
+            None,
+        );
+}
+
+#[test]
+fn internal_unbond_reduce_lock_period_test() {
+    let mut setup = SimpleLockEnergySetup::new(energy_factory::contract_obj);
+    let first_user = setup.first_user.clone();
+    let half_balance = USER_BALANCE / 2;
+
+    setup
+        .b_mock
+        .execute_tx(&setup.owner, &setup.sc_wrapper, &rust_biguint!(0), |sc| {
+            sc.set_unbond_mode(UnbondMode::Internal);
+        })
+        .assert_ok();
+
+    setup.b_mock.set_block_epoch(0);
+    setup
+        .lock(
+            &first_user,
+            BASE_ASSET_TOKEN_ID,
+            half_balance,
+            LOCK_OPTIONS[1],
+        )
+        .assert_ok();
+
+    // the penalty is burned here instead of being sent to the unstake SC
+    setup.b_mock.set_block_epoch(10);
+    let penalty_amount =
+        setup.get_penalty_amount(half_balance, LOCK_OPTIONS[1] - 10, EPOCHS_IN_YEAR - 10);
+    setup
+        .reduce_lock_period(&first_user, 1, half_balance, LOCK_OPTIONS[0])
+        .assert_ok();
+    setup
+        .b_mock
+        .execute_query(&setup.sc_wrapper, |sc| {
+            assert_eq!(
+                to_rust_biguint(sc.total_penalty_distributed(PenaltyDestination::Burn).get()),
+                penalty_amount
+            );
+            assert_eq!(
+                to_rust_biguint(sc.total_penalties_collected().get()),
+                penalty_amount
+            );
+        })
+        .assert_ok();
+}
@@ -0,0 +1,217 @@
+#![allow(deprecated)]
+
+mod energy_factory_setup;
+
+use energy_factory::{
+    energy::{Energy, EnergyModule},
+    locked_token_transfer::LockedTokenTransferModule,
+};
+use energy_factory_setup::*;
+use multiversx_sc::{
+    storage::{
+        mappers::{SingleValueMapper, StorageMapper},
+        StorageKey,
+    },
+    types::{BigInt, BigUint, ManagedAddress},
+};
+
+use multiversx_sc_scenario::{managed_address, managed_biguint, rust_biguint, DebugApi};
+
+#[test]
+fn energy_stops_depleting_at_unlock_epoch_test() {
+    let mut setup = SimpleLockEnergySetup::new(energy_factory::contract_obj);
+    let first_user = setup.first_user.clone();
+    let half_balance = USER_BALANCE / 2;
+
+    let mut current_epoch = 1;
+    setup.b_mock.set_block_epoch(current_epoch);
+
+    setup
+        .lock(
+            &first_user,
+            BASE_ASSET_TOKEN_ID,
+            half_balance,
+            LOCK_OPTIONS[0],
+        )
+        .assert_ok();
+    setup
+        .lock(
+            &first_user,
+            BASE_ASSET_TOKEN_ID,
+            half_balance,
+            LOCK_OPTIONS[1],
+        )
+        .assert_ok();
+
+    let first_unlock_epoch = to_start_of_month(current_epoch + LOCK_OPTIONS[0]);
+    let second_unlock_epoch = to_start_of_month(current_epoch + LOCK_OPTIONS[1]);
+
+    // first tokens reached their unlock epoch, so only the second ones still deplete energy
+    current_epoch = first_unlock_epoch + 40;
+    setup.b_mock.set_block_epoch(current_epoch);
+
+    let expected_energy = rust_biguint!(half_balance) * (second_unlock_epoch - current_epoch);
+    assert_eq!(setup.get_user_energy(&first_user), expected_energy);
+    assert_eq!(setup.get_total_energy(), expected_energy);
+
+    // unlocking matured tokens does not refund any energy
+    setup.unlock(&first_user, 1, half_balance).assert_ok();
+    assert_eq!(setup.get_user_energy(&first_user), expected_energy);
+    assert_eq!(setup.get_total_energy(), expected_energy);
+    assert_eq!(setup.get_total_locked_tokens(), rust_biguint!(half_balance));
+
+    // energy stays at zero once all tokens are unlockable
+    current_epoch = second_unlock_epoch + 100;
+    setup.b_mock.set_block_epoch(current_epoch);
+
+    setup
+        .b_mock
+        .execute_query(&setup.sc_wrapper, |sc| {
+            let energy = sc.get_updated_user_energy(&managed_address!(&first_user));
+            assert_eq!(
+                energy.get_energy_entry().get_energy_amount_raw(),
+                &BigInt::zero()
+            );
+            assert_eq!(energy.get_unlock_schedule().len(), 1);
+            assert_eq!(energy.get_untracked_locked_tokens(), BigUint::zero());
+        })
+        .assert_ok();
+}
+
+#[test]
+fn energy_entry_encoding_test() {
+    let mut setup = SimpleLockEnergySetup::new(energy_factory::contract_obj);
+    let first_user = setup.first_user.clone();
+    let half_balance = USER_BALANCE / 2;
+
+    let mut current_epoch = 1;
+    setup.b_mock.set_block_epoch(current_epoch);
+
+    // entry saved before unlock schedules existed
+    setup
+        .b_mock
+        .execute_tx(&setup.owner, &setup.sc_wrapper, &rust_biguint!(0), |sc| {
+            let energy_entry = Energy::new(
+                BigInt::from(managed_biguint!(half_balance) * 100u64),
+                current_epoch,
+                managed_biguint!(half_balance),
+            );
+            sc.user_energy(&managed_address!(&first_user))
+                .set(&energy_entry);
+
+            let energy = sc.get_updated_user_energy(&managed_address!(&first_user));
+            assert!(energy.get_unlock_schedule().is_empty());
+            assert_eq!(
+                energy.get_untracked_locked_tokens(),
+                managed_biguint!(half_balance)
+            );
+        })
+        .assert_ok();
+
+    // untracked tokens keep depleting energy
+    current_epoch += 10;
+    setup.b_mock.set_block_epoch(current_epoch);
+    assert_eq!(
+        setup.get_user_energy(&first_user),
+        rust_biguint!(half_balance) * 90u64
+    );
+
+    setup
+        .lock(
+            &first_user,
+            BASE_ASSET_TOKEN_ID,
+            half_balance,
+            LOCK_OPTIONS[0],
+        )
+        .assert_ok();
+
+    // the unlock schedule is saved separately, so the entry keeps the format other contracts decode
+    setup
+        .b_mock
+        .execute_query(&setup.sc_wrapper, |sc| {
+            let mut storage_key = StorageKey::<DebugApi>::new(b"userEnergy");
+            storage_key.append_item(&ManagedAddress::<DebugApi>::from(&first_user));
+            let entry_mapper: SingleValueMapper<
+                DebugApi,
+                (BigInt<DebugApi>, u64, BigUint<DebugApi>),
+            > = SingleValueMapper::new(storage_key);
+            let (_, last_update_epoch, total_locked_tokens) = entry_mapper.get();
+            assert_eq!(last_update_epoch, current_epoch);
+            assert_eq!(total_locked_tokens, managed_biguint!(USER_BALANCE));
+
+            let energy = sc.get_updated_user_energy(&managed_address!(&first_user));
+            assert_eq!(energy.get_unlock_schedule().len(), 1);
+            assert_eq!(
+                energy.get_untracked_locked_tokens(),
+                managed_biguint!(half_balance)
+            );
+        })
+        .assert_ok();
+}
+
+#[test]
+fn unlock_schedule_after_locked_token_transfer_test() {
+    let mut setup = SimpleLockEnergySetup::new(energy_factory::contract_obj);
+    let first_user = setup.first_user.clone();
+    let owner = setup.owner.clone();
+    let half_balance = USER_BALANCE / 2;
+
+    let current_epoch = 1;
+    setup.b_mock.set_block_epoch(current_epoch);
+
+    setup
+        .lock(
+            &first_user,
+            BASE_ASSET_TOKEN_ID,
+            half_balance,
+            LOCK_OPTIONS[0],
+        )
+        .assert_ok();
+    setup
+        .lock(
+            &first_user,
+            BASE_ASSET_TOKEN_ID,
+            half_balance,
+            LOCK_OPTIONS[2],
+        )
+        .assert_ok();
+
+    // the tokens unlocking last are transferred out,
+    // and the new entry is computed by the transferring contract
+    let first_unlock_epoch = to_start_of_month(current_epoch + LOCK_OPTIONS[0]);
+    let remaining_energy = rust_biguint!(half_balance) * (first_unlock_epoch - current_epoch);
+    setup
+        .b_mock
+        .execute_tx(&owner, &setup.sc_wrapper, &rust_biguint!(0), |sc| {
+            sc.token_transfer_whitelist().add(&managed_address!(&owner));
+            sc.set_user_energy_after_locked_token_transfer(
+                managed_address!(&first_user),
+                Energy::new(
+                    BigInt::from(BigUint::from_bytes_be(&remaining_energy.to_bytes_be())),
+                    current_epoch,
+                    managed_biguint!(half_balance),
+                ),
+            );
+
+            let energy = sc.get_updated_user_energy(&managed_address!(&first_user));
+            assert_eq!(energy.get_unlock_schedule().len(), 1);
+            assert_eq!(energy.get_untracked_locked_tokens(), managed_biguint!(0));
+        })
+        .assert_ok();
+
+    assert_eq!(setup.get_user_energy(&first_user), remaining_energy);
+    assert_eq!(setup.get_total_energy(), remaining_energy);
+
+    // only the remaining tokens deplete energy, so it stops at zero
+    setup.b_mock.set_block_epoch(first_unlock_epoch + 100);
+    setup
+        .b_mock
+        .execute_query(&setup.sc_wrapper, |sc| {
+            let energy = sc.get_updated_energy_entry_for_user(&managed_address!(&first_user));
+            assert_eq!(*energy.get_energy_amount_raw(), BigInt::zero());
+        })
+        .assert_ok();
+    assert_eq!(setup.get_user_energy(&first_user), rust_biguint!(0));
+    assert_eq!(setup.get_total_energy(), rust_biguint!(0));
+    assert_eq!(setup.get_total_locked_tokens(), rust_biguint!(half_balance));
+}
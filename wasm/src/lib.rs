@@ -6,9 +6,9 @@
 
 // Init:                                 1
 // Upgrade:                              1
-// Endpoints:                           33
+// Endpoints:                          101
 // Async Callback:                       1
-// Total number of exported functions:  36
+// Total number of exported functions: 104
 
 #![no_std]
 
@@ -29,15 +29,66 @@ multiversx_sc_wasm_adapter::endpoints! {
         getLegacyLockedTokenId => legacy_locked_token_id
         getEnergyEntryForUser => get_updated_energy_entry_for_user
         getEnergyAmountForUser => get_energy_amount_for_user
+        getEnergyEntriesForUsers => get_energy_entries_for_users
+        getEnergyAmountsForUsers => get_energy_amounts_for_users
+        getTotalEnergy => get_total_energy
+        getTotalLockedTokens => get_total_locked_tokens
+        getEnergyAtEpoch => get_energy_at_epoch
+        setEnergyDecayMode => set_energy_decay_mode
+        getEnergyDecayMode => energy_decay_mode
+        delegateEnergy => delegate_energy
+        undelegateEnergy => undelegate_energy
+        getEnergyDelegatee => get_energy_delegatee
+        getDelegatedEnergyForUser => get_delegated_energy_for_user
+        getEffectiveEnergyForUser => get_effective_energy_for_user
+        recomputeEnergy => recompute_energy
+        addEnergySubscriber => add_energy_subscriber
+        removeEnergySubscriber => remove_energy_subscriber
+        setEnergySubscriberEnabled => set_energy_subscriber_enabled
+        setEnergySubscriberGasLimit => set_energy_subscriber_gas_limit
+        getEnergySubscribers => get_energy_subscribers
+        setLockCancellationGracePeriod => set_lock_cancellation_grace_period
+        cancelLock => cancel_lock
+        getCancellableAmount => get_cancellable_amount
+        getLockCancellationGracePeriod => lock_cancellation_grace_period
+        getUnlockEpochRoundingMode => unlock_epoch_rounding_mode
+        isLockRangeModeEnabled => lock_range_mode_enabled
         addLockOptions => add_lock_options
+        removeLockOptions => remove_lock_options
+        updateLockOptionPenalty => update_lock_option_penalty
+        setLockOptionAvailability => set_lock_option_availability
+        scheduleLockOptions => schedule_lock_options
+        cancelScheduledLockOptions => cancel_scheduled_lock_options
+        setLockOptionsScheduleDelay => set_lock_options_schedule_delay
+        getLockOptionsScheduleDelay => get_lock_options_schedule_delay
+        setUnlockEpochGranularity => set_unlock_epoch_granularity
+        getUnlockEpochGranularity => get_unlock_epoch_granularity_view
+        setUnlockEpochRoundingMode => set_unlock_epoch_rounding_mode
+        setLockRangeMode => set_lock_range_mode
+        setLockOptionEnergyMultiplier => set_lock_option_energy_multiplier
+        getLockOptionEnergyMultiplier => get_lock_option_energy_multiplier
         getLockOptions => get_lock_options_view
+        getLockOptionsWithAvailability => get_lock_options_with_availability
+        getScheduledLockOptionsEpoch => get_scheduled_lock_options_epoch
+        getScheduledLockOptions => get_scheduled_lock_options_view
         unlockEarly => unlock_early
         reduceLockPeriod => reduce_lock_period
+        reduceLockPeriodToEpoch => reduce_lock_period_to_epoch
+        getUnlockEarlyQuote => get_unlock_early_quote
+        getReduceLockPeriodQuote => get_reduce_lock_period_quote
         getPenaltyAmount => calculate_penalty_amount
         setTokenUnstakeAddress => set_token_unstake_address
         revertUnstake => revert_unstake
         getTokenUnstakeScAddress => token_unstake_sc_address
+        setUnbondMode => set_unbond_mode
+        setUnbondEpochs => set_unbond_epochs
+        claimUnbonded => claim_unbonded
+        cancelUnbond => cancel_unbond
+        getUnbondMode => unbond_mode
+        getUnbondEpochs => unbond_epochs
+        getUnbondEntries => unbond_entries
         setEnergyForOldTokens => set_energy_for_old_tokens
+        addUsersToTotalEnergy => add_users_to_total_energy
         updateEnergyAfterOldTokenUnlock => update_energy_after_old_token_unlock
         migrateOldTokens => migrate_old_tokens
         pause => pause_endpoint
@@ -46,6 +97,23 @@ multiversx_sc_wasm_adapter::endpoints! {
         setTransferRoleLockedToken => set_transfer_role
         setBurnRoleLockedToken => set_burn_role
         mergeTokens => merge_tokens_endpoint
+        setPenaltyCurve => set_penalty_curve
+        getPenaltyCurve => penalty_curve
+        setPenaltyDiscountTiers => set_penalty_discount_tiers
+        getPenaltyDiscountForUser => get_penalty_discount_for_user
+        getPenaltyDiscountTiers => penalty_discount_tiers
+        getPenaltiesCollected => get_penalties_collected
+        getTotalPenaltiesCollected => total_penalties_collected
+        getPenaltiesCollectedForEpoch => penalties_collected_for_epoch
+        getPenaltiesCollectedForWeek => penalties_collected_for_week
+        setPenaltyDistribution => set_penalty_distribution
+        clearPenaltyDistribution => clear_penalty_distribution
+        setFeesCollectorAddress => set_fees_collector_address
+        setTreasuryAddress => set_treasury_address
+        getPenaltyDistribution => penalty_distribution
+        getFeesCollectorAddress => fees_collector_address
+        getTreasuryAddress => treasury_address
+        getTotalPenaltyDistributed => total_penalty_distributed
         lockVirtual => lock_virtual
         addSCAddressToWhitelist => add_sc_address_to_whitelist
         removeSCAddressFromWhitelist => remove_sc_address_from_whitelist